@@ -1,15 +1,18 @@
 use accumulator_storage::accumulator_storage::AccumulatorFileStorage;
+use accumulator_storage::sled_storage::AccumulatorSledStorage;
 use accumulator_trie::{
     epoch_sedes::EpochSede,
+    merkle_tree::get_epoch_tree,
     trie::{Trie, TrieTrait},
     {AccumulatorTrie, AccumulatorTrieTrait},
 };
-use config::Config;
+use config::{Config, StorageBackend};
 use db::block_db::{BlockDb, BlockDbTrait};
 use ethereum_types::{H256, U256};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::{thread, time};
+use tree_hash::TreeHash;
 
 pub fn run(config: Config) {
     //start ctrl+c handler
@@ -24,16 +27,65 @@ pub fn run(config: Config) {
     })
     .expect("Error setting Ctrl-C handler");
 
-    let accumulator_storage = AccumulatorFileStorage::new(
-        config.master_accumulator_file_path,
-        config.epoch_accumulator_file_path,
-    );
-
     //init db and accumulator trie structs
     let block_db = BlockDb::new(config.block_connection_string);
 
-    let trie = Trie::new(accumulator_storage);
-    let accumulator_trie = AccumulatorTrie::new(trie);
+    match config.storage_backend {
+        StorageBackend::File => {
+            let accumulator_storage = AccumulatorFileStorage::new(
+                config.master_accumulator_file_path,
+                config.epoch_accumulator_file_path,
+            )
+            .with_snappy_compression(config.snappy_compression);
+            let accumulator_trie = AccumulatorTrie::new(Trie::new(accumulator_storage));
+
+            //the manifest only covers the files of the file storage
+            //it is rewritten after every completed epoch and once more on exit
+            let write_manifest = || {
+                let write_manifest_result =
+                    accumulator_trie.trie.accumulator_storage.write_manifest();
+                println!("Write manifest result : {:?}", write_manifest_result);
+            };
+
+            run_accumulator(
+                &accumulator_trie,
+                &block_db,
+                &config.starting_block_number,
+                &running,
+                &write_manifest,
+            );
+            write_manifest();
+        }
+        StorageBackend::Sled => {
+            let accumulator_storage = AccumulatorSledStorage::open(&config.sled_db_path)
+                .expect("Unable to open the accumulator database");
+            let accumulator_trie = AccumulatorTrie::new(Trie::new(accumulator_storage));
+
+            run_accumulator(
+                &accumulator_trie,
+                &block_db,
+                &config.starting_block_number,
+                &running,
+                &|| (),
+            );
+        }
+    }
+
+    println!("Exited succesfully!");
+}
+
+//appends new blocks to the accumulator until ctrl+c is pressed
+//on_epoch_completed is called once the last block of an epoch is stored
+fn run_accumulator<T: TrieTrait>(
+    accumulator_trie: &AccumulatorTrie<T>,
+    block_db: &BlockDb,
+    starting_block_number: &u32,
+    running: &AtomicUsize,
+    on_epoch_completed: &dyn Fn(),
+) {
+    //finish the last commit if the previous run stopped in the middle of it
+    let recover_result = accumulator_trie.recover_accumulators();
+    println!("Recover accumulators result : {:?}", recover_result);
 
     //get current master and epoch trie
     let mut master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
@@ -42,18 +94,18 @@ pub fn run(config: Config) {
     //if epoch number is 0, we can say that everything is suppose to be empty
     let mut current_epoch_accumulator: Vec<EpochSede> = vec![];
     if epoch_number > 0 {
-        current_epoch_accumulator = accumulator_trie
-            .get_epoch_accumulator_by_epoch_number(&epoch_number)
-            .unwrap();
-
         //check hash to see if the loaded accumulators are correct
-        let epoch_hash = accumulator_trie.get_tree_root_hash(&current_epoch_accumulator);
-        let stored_hash = master_accumulator[epoch_number -1];
-
         //panic if invalid and advise user to clear all files
-        if epoch_hash != stored_hash {
-            panic!("Opps, hash mismatched. You may need to clear all files and try again");
+        current_epoch_accumulator = accumulator_trie
+            .get_current_epoch_accumulator(&master_accumulator)
+            .expect("Opps, hash mismatched. You may need to clear all files and try again");
+
+        //record the master root of completed epochs that are missing from the history
+        let mut completed_epochs = epoch_number - 1;
+        if current_epoch_accumulator.len() as u32 == accumulator_trie.epoch_size {
+            completed_epochs = epoch_number;
         }
+        accumulator_trie.store_historical_master_roots(&master_accumulator[..completed_epochs]);
     } else {
         //lets read the file to create an empty epoch 1 file
         epoch_number = 1;
@@ -62,8 +114,11 @@ pub fn run(config: Config) {
             .expect("something is wrong with the init epoch file");
     }
 
+    //internal nodes of the current epoch are cached so each block only rehashes its own path
+    let mut epoch_tree = get_epoch_tree(&current_epoch_accumulator);
+
     let mut starting_block_number = get_starting_block_number(
-        &config.starting_block_number,
+        starting_block_number,
         &(epoch_number as u32),
         &accumulator_trie.epoch_size,
         &(current_epoch_accumulator.len() as u32),
@@ -75,6 +130,10 @@ pub fn run(config: Config) {
         let blocks = block_db.get_blocks(&(starting_block_number as u64), &500);
 
         if let None = blocks {
+            //ctrl+c is also honoured while waiting for new blocks
+            if running.load(Ordering::SeqCst) > 0 {
+                return;
+            }
             println!("Loop completed, sleep for 5 seconds");
             let sleep_duration = time::Duration::from_millis(5000);
             thread::sleep(sleep_duration);
@@ -83,8 +142,7 @@ pub fn run(config: Config) {
 
         for block in blocks.unwrap().iter() {
             if running.load(Ordering::SeqCst) > 0 {
-                println!("Exited succesfully!");
-                std::process::exit(0);
+                return;
             }
             println!("Processing for block number: {}", block.block_number);
 
@@ -95,8 +153,16 @@ pub fn run(config: Config) {
                 //increment epoch number and create a new epoch trie
 
                 println!("{:?}", current_epoch_accumulator.len());
+                let store_history_result =
+                    accumulator_trie.store_historical_master_roots(&master_accumulator);
+                println!(
+                    "Store master root history result : {:?}",
+                    store_history_result
+                );
+
                 epoch_number = epoch_number + 1;
                 current_epoch_accumulator = vec![];
+                epoch_tree = get_epoch_tree(&current_epoch_accumulator);
                 to_append_master_accumulator = true;
 
                 println!("{:?}", epoch_number);
@@ -123,8 +189,9 @@ pub fn run(config: Config) {
             //if this round starts with a new epoch trie
             let epoch_sede = EpochSede::new(total_difficulty, typed_block_hash);
             current_epoch_accumulator.push(epoch_sede);
+            epoch_tree.push(epoch_sede.tree_hash_root());
 
-            let epoch_hash = accumulator_trie.get_tree_root_hash(&current_epoch_accumulator);
+            let epoch_hash = epoch_tree.hash_tree_root();
 
             if !to_append_master_accumulator {
                 //remove and insert to last element
@@ -132,17 +199,23 @@ pub fn run(config: Config) {
             }
             master_accumulator.push(epoch_hash);
 
-            //store both of them
-            let store_epoch_result = accumulator_trie
-                .store_epoch_accumulator_by_epoch_number(&epoch_number, &current_epoch_accumulator);
-            let store_master_result =
-                accumulator_trie.store_master_accumulator(&master_accumulator);
+            //store both of them in one commit, the stored master root is returned
+            let master_root = accumulator_trie.store_epoch_and_master_accumulator(
+                &epoch_number,
+                &current_epoch_accumulator,
+                &master_accumulator,
+            );
 
             println!(
-                "Store epoch result : {:?}, epoch number : {:?}",
-                store_epoch_result, epoch_number
+                "Store epoch and master result : {:?}, epoch number : {:?}",
+                master_root.is_some(),
+                epoch_number
             );
-            println!("Store master result : {:?}", store_master_result);
+            println!("Master root : {:?}", master_root);
+
+            if current_epoch_accumulator.len() as u32 == accumulator_trie.epoch_size {
+                on_epoch_completed();
+            }
 
             //we udpate the latest block number here
             starting_block_number = block_number + 1;
@@ -1,20 +1,72 @@
 use accumulator_storage::accumulator_storage::AccumulatorFileStorage;
+use accumulator_storage::sled_storage::AccumulatorSledStorage;
 use accumulator_trie::trie::{Trie, TrieTrait};
 use accumulator_trie::{AccumulatorTrie, AccumulatorTrieTrait};
-use config::Config;
+use config::{Config, StorageBackend};
 use ssz_types::{typenum, VariableList};
 use tree_hash::TreeHash;
 use typenum::U2048;
 
-pub fn run(config: Config) {
+pub fn run(config: Config, args: Vec<String>) {
+    if config.storage_backend == StorageBackend::Sled {
+        let accumulator_storage = AccumulatorSledStorage::open(&config.sled_db_path)
+            .expect("Unable to open the accumulator database");
+        let accumulator_trie = AccumulatorTrie::new(Trie::new(accumulator_storage));
+        recover_accumulators(&accumulator_trie);
+
+        match args.first().map(|x| x.as_str()) {
+            Some("write-manifest") | Some("verify-manifest") => {
+                println!("The manifest is only written for the file storage")
+            }
+            _ => check_hash(&accumulator_trie),
+        }
+        return;
+    }
+
     let accumulator_storage = AccumulatorFileStorage::new(
         config.master_accumulator_file_path,
         config.epoch_accumulator_file_path,
-    );
+    )
+    .with_snappy_compression(config.snappy_compression);
+    let accumulator_trie = AccumulatorTrie::new(Trie::new(accumulator_storage));
+    recover_accumulators(&accumulator_trie);
 
-    let trie = Trie::new(accumulator_storage);
-    let accumulator_trie = AccumulatorTrie::new(trie);
+    //the manifest only compares files, the accumulators are not decoded
+    let accumulator_storage = &accumulator_trie.trie.accumulator_storage;
+    match args.first().map(|x| x.as_str()) {
+        Some("write-manifest") => {
+            println!(
+                "Write manifest result : {:?}",
+                accumulator_storage.write_manifest()
+            );
+            return;
+        }
+        Some("verify-manifest") => {
+            match accumulator_storage.verify_manifest() {
+                Ok(x) => println!("All {} files matched the manifest!", x),
+                Err(errors) => {
+                    for error in errors.iter() {
+                        println!("{}", error);
+                    }
+                    println!("Files do not match the manifest :(");
+                }
+            }
+            return;
+        }
+        _ => (),
+    }
 
+    check_hash(&accumulator_trie);
+}
+
+//a commit that was interrupted is finished first, so nothing is read or listed half written
+fn recover_accumulators<T: TrieTrait>(accumulator_trie: &AccumulatorTrie<T>) {
+    if !accumulator_trie.recover_accumulators() {
+        panic!("Unable to recover the accumulators from the journal");
+    }
+}
+
+fn check_hash<T: TrieTrait>(accumulator_trie: &AccumulatorTrie<T>) {
     let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
 
     let mut is_all_hash_matched = true;
@@ -49,4 +101,13 @@ pub fn run(config: Config) {
     } else {
         println!("All hash matched!");
     }
+
+    let master_root = accumulator_trie.get_master_root_hash(&master_accumulator);
+    println!("Master accumulator root: {:?}", master_root);
+
+    match accumulator_trie.get_master_accumulator_root() {
+        Some(x) if x == master_root => println!("Stored master accumulator root matched!"),
+        Some(x) => println!("Stored master accumulator root does not match: {:?}", x),
+        None => println!("No stored master accumulator root found"),
+    }
 }
@@ -4,6 +4,7 @@ fn main() {
     let config_file_name = "config.json";
 
     let config = Config::new(&config_file_name);
-    accumulator_check_hash::run(config);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    accumulator_check_hash::run(config, args);
     
 }
\ No newline at end of file
@@ -1,10 +1,23 @@
-use std::fs::File;
+use crate::accumulator_store::{AccumulatorStore, MASTER_ROOT_HISTORY_NAME, MASTER_ROOT_NAME};
+use snap::read::FrameDecoder;
+use snap::write::FrameEncoder;
+use std::fs::{File, OpenOptions};
 use std::io::prelude::*;
+use std::path::Path;
+
+//every snappy framed stream starts with this stream identifier chunk
+const SNAPPY_STREAM_IDENTIFIER: [u8; 10] =
+    [0xff, 0x06, 0x00, 0x00, b's', b'N', b'a', b'P', b'p', b'Y'];
+
+//content is written here first and renamed over the file once it is on disk
+const TEMP_FILE_EXTENSION: &str = ".tmp";
 
 #[derive(Debug)]
 pub struct AccumulatorFileStorage {
     pub master_file_path: String,
     pub epoch_file_path: String,
+    //only affects writes, reads detect the format from the file content
+    pub snappy_compression: bool,
 }
 
 impl AccumulatorFileStorage {
@@ -12,35 +25,41 @@ impl AccumulatorFileStorage {
         AccumulatorFileStorage {
             master_file_path: mas_file_path,
             epoch_file_path: epoch_file_path,
+            snappy_compression: false,
         }
     }
 
-    pub fn get_master_accumulator(&self, file_name: &str) -> Vec<u8> {
-        let full_path = &self.concat_file_path(&self.master_file_path[..], file_name);
-        self.read_from_file(full_path)
+    pub fn with_snappy_compression(mut self, snappy_compression: bool) -> AccumulatorFileStorage {
+        self.snappy_compression = snappy_compression;
+        self
     }
 
-    pub fn get_epoch_accumulator(&self, file_name: &str) -> Vec<u8> {
-        let full_path = &self.concat_file_path(&self.epoch_file_path[..], file_name);
-        self.read_from_file(full_path)
-    }
+    fn encode_content(&self, file_name: &str, content: &[u8]) -> Vec<u8> {
+        //the root is read as hex text and the root history is appended to
+        let is_raw_file = file_name == MASTER_ROOT_NAME || file_name == MASTER_ROOT_HISTORY_NAME;
+        if !self.snappy_compression || is_raw_file {
+            return content.to_vec();
+        }
 
-    pub fn write_master_accumulator(&self, file_name: &str, content: &Vec<u8>) -> bool {
-        let full_path = &self.concat_file_path(&self.master_file_path[..], file_name);
-        self.write_to_file(full_path, content)
+        let mut encoder = FrameEncoder::new(Vec::new());
+        encoder.write_all(content).expect("Unable to compress");
+        encoder.into_inner().expect("Unable to compress")
     }
 
-    pub fn write_epoch_accumulator(&self, file_name: &str, content: &Vec<u8>) -> bool {
-        let full_path = &self.concat_file_path(&self.epoch_file_path[..], file_name);
-        self.write_to_file(full_path, content)
-    }
+    //a stream that is cut short or corrupt is an error, it must not be mistaken for an empty file
+    fn decode_content(&self, content: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        if !content.starts_with(&SNAPPY_STREAM_IDENTIFIER) {
+            return Ok(content);
+        }
 
-    pub fn create_new_master_accumulator_file(&self, file_name: &str) -> bool {
-        self.create_file_at_path(&self.master_file_path, file_name)
+        let mut decompressed = Vec::new();
+        FrameDecoder::new(&content[..]).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
     }
 
-    pub fn create_new_epoch_accumulator_file(&self, file_name: &str) -> bool {
-        self.create_file_at_path(&self.epoch_file_path, file_name)
+    fn read_and_decode(&self, path: &str) -> Result<Vec<u8>, String> {
+        self.decode_content(self.read_from_file(path))
+            .map_err(|x| format!("Unable to decompress {}: {}", path, x))
     }
 
     fn read_from_file(&self, path: &str) -> Vec<u8> {
@@ -55,19 +74,76 @@ impl AccumulatorFileStorage {
         file_content
     }
 
-    fn write_to_file(&self, path: &str, content: &Vec<u8>) -> bool {
+    fn write_to_file(&self, path: &str, content: &[u8]) -> bool {
         let is_exist = std::path::Path::new(&path).exists();
         if !is_exist {
             return false;
         }
 
-        let write_result = std::fs::write(path, content);
-        let write_result = match write_result {
+        self.replace_file(path, content)
+    }
+
+    //a crash leaves either the old or the new content, never a truncated file
+    pub(crate) fn replace_file(&self, path: &str, content: &[u8]) -> bool {
+        let temp_path = format!("{}{}", path, TEMP_FILE_EXTENSION);
+        let write_result = self
+            .write_and_sync(&temp_path, content)
+            .and_then(|_| std::fs::rename(&temp_path, path))
+            .and_then(|_| self.sync_parent_dir(path));
+
+        match write_result {
             Ok(_) => true,
-            Err(_) => return false,
+            Err(_) => {
+                let _ = std::fs::remove_file(&temp_path);
+                false
+            }
+        }
+    }
+
+    fn write_and_sync(&self, path: &str, content: &[u8]) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(content)?;
+        file.sync_all()
+    }
+
+    //the rename is only durable once the directory entry is synced
+    fn sync_parent_dir(&self, path: &str) -> std::io::Result<()> {
+        let parent = match Path::new(path).parent() {
+            Some(x) if !x.as_os_str().is_empty() => x,
+            _ => Path::new("."),
         };
 
-        write_result
+        File::open(parent)?.sync_all()
+    }
+
+    fn append_to_file(&self, path: &str, content: &[u8]) -> bool {
+        let file = OpenOptions::new().append(true).open(path);
+        match file {
+            Ok(mut file) => file
+                .write_all(content)
+                .and_then(|_| file.sync_all())
+                .is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    //sorted names of the files in a folder, an empty list if the folder does not exist
+    fn list_file_names(&self, base_path: &str) -> Vec<String> {
+        let read_dir = match std::fs::read_dir(base_path) {
+            Ok(x) => x,
+            Err(_) => return vec![],
+        };
+
+        let mut file_names: Vec<String> = read_dir
+            .filter_map(|x| x.ok())
+            .filter(|x| x.path().is_file())
+            .filter_map(|x| x.file_name().into_string().ok())
+            //left over by a write that did not finish
+            .filter(|x| !x.ends_with(TEMP_FILE_EXTENSION))
+            .collect();
+        file_names.sort();
+
+        file_names
     }
 
     pub fn concat_file_path(&self, base_path: &str, file_name: &str) -> String {
@@ -100,9 +176,54 @@ impl AccumulatorFileStorage {
     }
 }
 
+impl AccumulatorStore for AccumulatorFileStorage {
+    fn get_master_accumulator(&self, file_name: &str) -> Result<Vec<u8>, String> {
+        let full_path = &self.concat_file_path(&self.master_file_path[..], file_name);
+        self.read_and_decode(full_path)
+    }
+
+    fn get_epoch_accumulator(&self, file_name: &str) -> Result<Vec<u8>, String> {
+        let full_path = &self.concat_file_path(&self.epoch_file_path[..], file_name);
+        self.read_and_decode(full_path)
+    }
+
+    fn write_master_accumulator(&self, file_name: &str, content: &[u8]) -> bool {
+        let full_path = &self.concat_file_path(&self.master_file_path[..], file_name);
+        self.write_to_file(full_path, &self.encode_content(file_name, content))
+    }
+
+    fn write_epoch_accumulator(&self, file_name: &str, content: &[u8]) -> bool {
+        let full_path = &self.concat_file_path(&self.epoch_file_path[..], file_name);
+        self.write_to_file(full_path, &self.encode_content(file_name, content))
+    }
+
+    //appended content is never compressed since a framed stream cannot be extended in place
+    fn append_master_accumulator(&self, file_name: &str, content: &[u8]) -> bool {
+        let full_path = &self.concat_file_path(&self.master_file_path[..], file_name);
+        self.append_to_file(full_path, content)
+    }
+
+    fn create_new_master_accumulator_file(&self, file_name: &str) -> bool {
+        self.create_file_at_path(&self.master_file_path, file_name)
+    }
+
+    fn create_new_epoch_accumulator_file(&self, file_name: &str) -> bool {
+        self.create_file_at_path(&self.epoch_file_path, file_name)
+    }
+
+    fn list_master_accumulators(&self) -> Vec<String> {
+        self.list_file_names(&self.master_file_path)
+    }
+
+    fn list_epoch_accumulators(&self) -> Vec<String> {
+        self.list_file_names(&self.epoch_file_path)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::accumulator_store::{encode_journal, JournalEntry, JOURNAL_NAME};
     use serial_test::serial;
     use std::path::PathBuf;
 
@@ -110,6 +231,7 @@ mod tests {
         let storage = AccumulatorFileStorage {
             master_file_path: String::from("path_master"),
             epoch_file_path: String::from("path_epoch"),
+            snappy_compression: false,
         };
 
         storage
@@ -160,14 +282,14 @@ mod tests {
         let file_name = "test.txt";
 
         create_file_assert_delete_file(&storage.master_file_path, &file_name, |x| {
-            storage.get_master_accumulator(x)
+            storage.get_master_accumulator(x).unwrap()
         })
     }
 
     #[test]
     fn return_master_accumulator_none_if_file_doest_not_exist() {
         let storage = init_acculumulator_file_storage();
-        let storage_result = storage.get_master_accumulator("fileNotFound.txt");
+        let storage_result = storage.get_master_accumulator("fileNotFound.txt").unwrap();
 
         assert_eq!(&storage_result[..], []);
     }
@@ -219,14 +341,14 @@ mod tests {
         let file_name = "test.txt";
 
         create_file_assert_delete_file(&storage.epoch_file_path, &file_name, |x| {
-            storage.get_epoch_accumulator(x)
+            storage.get_epoch_accumulator(x).unwrap()
         })
     }
 
     #[test]
     fn return_epoch_accumulator_none_if_file_doest_not_exist() {
         let storage = init_acculumulator_file_storage();
-        let storage_result = storage.get_epoch_accumulator("fileNotFound.txt");
+        let storage_result = storage.get_epoch_accumulator("fileNotFound.txt").unwrap();
 
         assert_eq!(storage_result, [].to_vec());
     }
@@ -274,6 +396,219 @@ mod tests {
         assert_eq!(result, false);
     }
 
+    #[test]
+    fn append_to_file_fail_if_file_does_not_exist() {
+        let file_name = "this_file_does_not_exist_either.txt";
+        let storage = init_acculumulator_file_storage();
+
+        let result = storage.append_to_file(file_name, &[0, 1, 3]);
+
+        assert!(!result);
+    }
+
+    #[test]
+    fn append_to_file_and_get_correct_value() {
+        let file_name = "test_append_value.txt";
+        let base_path = ".";
+        let storage = init_acculumulator_file_storage();
+
+        create_file(&base_path, &file_name, "");
+
+        assert!(storage.append_to_file(file_name, &[0, 1]));
+        assert!(storage.append_to_file(file_name, &[3]));
+
+        let vec_result = storage.read_from_file(file_name);
+        assert_eq!(vec![0, 1, 3], vec_result);
+
+        std::fs::remove_file(&file_name).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn write_snappy_compressed_epoch_accumulator_and_read_back() {
+        let storage = init_acculumulator_file_storage().with_snappy_compression(true);
+        let file_name = "test_snappy_epoch.txt";
+        let full_path = storage.concat_file_path(&storage.epoch_file_path, &file_name);
+        let content: Vec<u8> = vec![7; 100];
+
+        storage.create_new_epoch_accumulator_file(&file_name);
+        assert!(storage.write_epoch_accumulator(file_name, &content));
+
+        let file_content = std::fs::read(&full_path).unwrap();
+        assert!(file_content.starts_with(&SNAPPY_STREAM_IDENTIFIER));
+        assert!(file_content.len() < content.len());
+
+        assert_eq!(content, storage.get_epoch_accumulator(file_name).unwrap());
+
+        //the format is detected on read regardless of the write mode
+        let storage = init_acculumulator_file_storage();
+        assert_eq!(content, storage.get_epoch_accumulator(file_name).unwrap());
+
+        delete_file(&storage.epoch_file_path, &full_path);
+    }
+
+    #[test]
+    #[serial]
+    fn read_uncompressed_master_accumulator_with_snappy_compression() {
+        let storage = init_acculumulator_file_storage();
+        let file_name = "test_uncompressed_master.txt";
+        let full_path = storage.concat_file_path(&storage.master_file_path, &file_name);
+        let content: Vec<u8> = vec![1, 2, 3];
+
+        storage.create_new_master_accumulator_file(&file_name);
+        assert!(storage.write_master_accumulator(file_name, &content));
+        assert_eq!(content, std::fs::read(&full_path).unwrap());
+
+        let storage = storage.with_snappy_compression(true);
+        assert_eq!(content, storage.get_master_accumulator(file_name).unwrap());
+
+        delete_file(&storage.master_file_path, &full_path);
+    }
+
+    #[test]
+    #[serial]
+    fn root_files_are_written_raw_with_snappy_compression() {
+        let storage = init_acculumulator_file_storage().with_snappy_compression(true);
+        let content = b"0x60f1".to_vec();
+
+        for file_name in [MASTER_ROOT_NAME, MASTER_ROOT_HISTORY_NAME] {
+            let full_path = storage.concat_file_path(&storage.master_file_path, file_name);
+            storage.create_new_master_accumulator_file(file_name);
+            assert!(storage.write_master_accumulator(file_name, &content));
+            assert_eq!(content, std::fs::read(&full_path).unwrap());
+
+            delete_file(&storage.master_file_path, &full_path);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn truncated_snappy_file_is_an_error() {
+        let storage = init_acculumulator_file_storage().with_snappy_compression(true);
+        let file_name = "test_truncated_snappy.txt";
+        let full_path = storage.concat_file_path(&storage.master_file_path, file_name);
+        let content: Vec<u8> = (0..50 * 32).map(|x| x as u8).collect();
+
+        storage.create_new_master_accumulator_file(file_name);
+        assert!(storage.write_master_accumulator(file_name, &content));
+
+        //a write cut short at the end of the stream
+        let compressed = std::fs::read(&full_path).unwrap();
+        std::fs::write(&full_path, &compressed[..compressed.len() - 7]).unwrap();
+
+        assert!(storage.get_master_accumulator(file_name).is_err());
+
+        delete_file(&storage.master_file_path, &full_path);
+    }
+
+    #[test]
+    fn write_to_file_keeps_old_content_when_write_fails() {
+        let file_name = "test_failed_write.txt";
+        let temp_path = format!("{}{}", file_name, TEMP_FILE_EXTENSION);
+        let storage = init_acculumulator_file_storage();
+
+        create_file(".", file_name, "old content");
+        //the temp file cannot be created when a folder is in its place
+        std::fs::create_dir_all(&temp_path).unwrap();
+
+        let write_result = storage.write_to_file(file_name, b"new content");
+        assert!(!write_result);
+        assert_eq!(b"old content".to_vec(), storage.read_from_file(file_name));
+
+        std::fs::remove_dir(&temp_path).unwrap();
+        std::fs::remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn partial_write_is_ignored_and_replaced_by_next_write() {
+        let storage = init_acculumulator_file_storage();
+        let file_name = "test_partial_write.txt";
+        let full_path = storage.concat_file_path(&storage.master_file_path, file_name);
+        let temp_path = format!("{}{}", full_path, TEMP_FILE_EXTENSION);
+        let content: Vec<u8> = (0..64).collect();
+
+        storage.create_new_master_accumulator_file(file_name);
+        assert!(storage.write_master_accumulator(file_name, &content));
+
+        //a crash in the middle of the next write leaves a truncated temp file behind
+        let new_content: Vec<u8> = (64..128).collect();
+        std::fs::write(&temp_path, &new_content[..10]).unwrap();
+
+        assert_eq!(content, storage.get_master_accumulator(file_name).unwrap());
+        assert_eq!(
+            vec![String::from(file_name)],
+            storage.list_master_accumulators()
+        );
+
+        assert!(storage.write_master_accumulator(file_name, &new_content));
+        assert_eq!(
+            new_content,
+            storage.get_master_accumulator(file_name).unwrap()
+        );
+        assert!(!std::path::Path::new(&temp_path).exists());
+
+        delete_file(&storage.master_file_path, &full_path);
+    }
+
+    #[test]
+    #[serial]
+    fn recover_replays_journal_left_by_interrupted_commit() {
+        let storage = init_acculumulator_file_storage();
+        let master_name = "test_journal_master.txt";
+        let epoch_name = "test_journal_epoch.txt";
+        let entries = [JournalEntry::Epoch {
+            name: String::from(epoch_name),
+            content: vec![1, 2, 3],
+        }];
+        let commit_point = JournalEntry::Master {
+            name: String::from(master_name),
+            content: vec![4, 5],
+        };
+
+        let journal = encode_journal(&entries, &commit_point);
+
+        //the journal is on disk but the commit point was not written, so the commit never happened
+        storage.create_new_master_accumulator_file(JOURNAL_NAME);
+        assert!(storage.write_master_accumulator(JOURNAL_NAME, &journal));
+
+        assert!(storage.recover());
+        assert!(storage
+            .get_epoch_accumulator(epoch_name)
+            .unwrap()
+            .is_empty());
+        assert!(storage
+            .get_master_accumulator(JOURNAL_NAME)
+            .unwrap()
+            .is_empty());
+
+        //the commit point was written, the other entries were not
+        assert!(storage.write_master_accumulator(JOURNAL_NAME, &journal));
+        storage.create_new_master_accumulator_file(master_name);
+        assert!(storage.write_master_accumulator(master_name, &[4, 5]));
+
+        assert!(storage.recover());
+        assert_eq!(
+            vec![1, 2, 3],
+            storage.get_epoch_accumulator(epoch_name).unwrap()
+        );
+        assert_eq!(
+            vec![4, 5],
+            storage.get_master_accumulator(master_name).unwrap()
+        );
+        assert!(storage
+            .get_master_accumulator(JOURNAL_NAME)
+            .unwrap()
+            .is_empty());
+
+        let journal_path = storage.concat_file_path(&storage.master_file_path, JOURNAL_NAME);
+        let master_path = storage.concat_file_path(&storage.master_file_path, master_name);
+        let epoch_path = storage.concat_file_path(&storage.epoch_file_path, epoch_name);
+        std::fs::remove_file(&journal_path).unwrap();
+        delete_file(&storage.epoch_file_path, &epoch_path);
+        delete_file(&storage.master_file_path, &master_path);
+    }
+
     #[test]
     fn write_to_file_and_get_correct_value() {
         let file_name = "test_correct_value.txt";
@@ -0,0 +1,249 @@
+use eth2_hashing::hash;
+use std::convert::TryInto;
+
+//blob in the master folder holding the writes of the commit in progress
+pub const JOURNAL_NAME: &str = "accumulator_journal.txt";
+
+pub const MASTER_ACCUMULATOR_NAME: &str = "master_accumulator.txt";
+
+//blobs in the master folder that are read as text or appended to, never compressed
+pub const MASTER_ROOT_NAME: &str = "master_accumulator_root.txt";
+pub const MASTER_ROOT_HISTORY_NAME: &str = "master_accumulator_root_history.txt";
+
+//where the encoded master and epoch accumulators are kept, blobs are addressed by name
+//a blob has to be created before it can be written or appended to
+//a blob that does not exist is read as empty, one that exists but cannot be read is an error
+pub trait AccumulatorStore {
+    fn get_master_accumulator(&self, name: &str) -> Result<Vec<u8>, String>;
+    fn get_epoch_accumulator(&self, name: &str) -> Result<Vec<u8>, String>;
+    fn write_master_accumulator(&self, name: &str, content: &[u8]) -> bool;
+    fn write_epoch_accumulator(&self, name: &str, content: &[u8]) -> bool;
+    fn append_master_accumulator(&self, name: &str, content: &[u8]) -> bool;
+    //returns false if the blob already exists
+    fn create_new_master_accumulator_file(&self, name: &str) -> bool;
+    fn create_new_epoch_accumulator_file(&self, name: &str) -> bool;
+    fn list_master_accumulators(&self) -> Vec<String>;
+    fn list_epoch_accumulators(&self) -> Vec<String>;
+
+    //the last entry is the commit point, it is written in place rather than into the journal
+    //the journal holds the other entries and the hash of the commit point, and is written first
+    //a crash before the commit point is written leaves the old state, a crash after is finished by recover
+    //relies on every blob, the journal included, being written atomically
+    fn commit(&self, entries: &[JournalEntry]) -> bool {
+        let (commit_point, journal_entries) = match entries.split_last() {
+            Some(x) => x,
+            None => return true,
+        };
+
+        self.create_new_master_accumulator_file(JOURNAL_NAME);
+        let journal = encode_journal(journal_entries, commit_point);
+        if !self.write_master_accumulator(JOURNAL_NAME, &journal) {
+            return false;
+        }
+
+        apply_journal_entries(self, std::slice::from_ref(commit_point))
+            && apply_journal_entries(self, journal_entries)
+            && self.write_master_accumulator(JOURNAL_NAME, &[])
+    }
+
+    //replays the journal left by an unfinished commit if its commit point was written
+    //a journal that cannot be decoded or whose commit point was not written is dropped
+    fn recover(&self) -> bool {
+        //an unreadable journal is kept, since it may hold the only copy of the last commit
+        let journal = match self.get_master_accumulator(JOURNAL_NAME) {
+            Ok(x) => x,
+            Err(_) => return false,
+        };
+        if journal.is_empty() {
+            return true;
+        }
+
+        let result = match decode_journal(&journal) {
+            Some((entries, commit_point)) => match is_commit_point_written(self, &commit_point) {
+                Ok(true) => apply_journal_entries(self, &entries),
+                Ok(false) => true,
+                Err(_) => return false,
+            },
+            None => true,
+        };
+
+        result && self.write_master_accumulator(JOURNAL_NAME, &[])
+    }
+}
+
+pub fn get_epoch_accumulator_name(epoch_number: &usize) -> String {
+    format!("epoch_accumulator_{}.txt", epoch_number)
+}
+
+//the reverse of get_epoch_accumulator_name, none for any other name
+pub fn parse_epoch_accumulator_name(name: &str) -> Option<u64> {
+    let epoch_number = name
+        .strip_prefix("epoch_accumulator_")?
+        .strip_suffix(".txt")?;
+    match epoch_number.chars().all(|x| x.is_ascii_digit()) {
+        true => epoch_number.parse().ok(),
+        false => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JournalEntry {
+    Master { name: String, content: Vec<u8> },
+    Epoch { name: String, content: Vec<u8> },
+}
+
+fn apply_journal_entries<S: AccumulatorStore + ?Sized>(
+    store: &S,
+    entries: &[JournalEntry],
+) -> bool {
+    entries.iter().all(|entry| match entry {
+        JournalEntry::Master { name, content } => {
+            store.create_new_master_accumulator_file(name);
+            store.write_master_accumulator(name, content)
+        }
+        JournalEntry::Epoch { name, content } => {
+            store.create_new_epoch_accumulator_file(name);
+            store.write_epoch_accumulator(name, content)
+        }
+    })
+}
+
+//the commit point of a journal holds the hash of the content instead of the content
+fn is_commit_point_written<S: AccumulatorStore + ?Sized>(
+    store: &S,
+    commit_point: &JournalEntry,
+) -> Result<bool, String> {
+    let (content, content_hash) = match commit_point {
+        JournalEntry::Master { name, content } => (store.get_master_accumulator(name)?, content),
+        JournalEntry::Epoch { name, content } => (store.get_epoch_accumulator(name)?, content),
+    };
+
+    Ok(hash(&content) == *content_hash)
+}
+
+//the number of entries as u64 le, then per entry a kind byte (0 master, 1 epoch)
+//followed by the name and content, both prefixed by their u64 le length
+//the commit point is the last entry, with the sha256 of its content in place of the content
+pub fn encode_journal(entries: &[JournalEntry], commit_point: &JournalEntry) -> Vec<u8> {
+    let hashed_commit_point = match commit_point {
+        JournalEntry::Master { name, content } => JournalEntry::Master {
+            name: name.clone(),
+            content: hash(content),
+        },
+        JournalEntry::Epoch { name, content } => JournalEntry::Epoch {
+            name: name.clone(),
+            content: hash(content),
+        },
+    };
+
+    let mut encoded = (entries.len() as u64 + 1).to_le_bytes().to_vec();
+    for entry in entries.iter().chain(std::iter::once(&hashed_commit_point)) {
+        let (kind, name, content) = match entry {
+            JournalEntry::Master { name, content } => (0, name, content),
+            JournalEntry::Epoch { name, content } => (1, name, content),
+        };
+
+        encoded.push(kind);
+        encoded.extend_from_slice(&(name.len() as u64).to_le_bytes());
+        encoded.extend_from_slice(name.as_bytes());
+        encoded.extend_from_slice(&(content.len() as u64).to_le_bytes());
+        encoded.extend_from_slice(content);
+    }
+
+    encoded
+}
+
+//returns the entries and the commit point, which still holds the hash of its content
+pub fn decode_journal(encoded: &[u8]) -> Option<(Vec<JournalEntry>, JournalEntry)> {
+    if encoded.len() < 8 {
+        return None;
+    }
+
+    let (count, mut rest) = encoded.split_at(8);
+    let count = u64::from_le_bytes(count.try_into().ok()?) as usize;
+
+    let mut entries = vec![];
+    while let Some((kind, remaining)) = rest.split_first() {
+        let (name, remaining) = split_length_prefixed(remaining)?;
+        let (content, remaining) = split_length_prefixed(remaining)?;
+        rest = remaining;
+
+        let name = String::from_utf8(name.to_vec()).ok()?;
+        let content = content.to_vec();
+        entries.push(match kind {
+            0 => JournalEntry::Master { name, content },
+            1 => JournalEntry::Epoch { name, content },
+            _ => return None,
+        });
+    }
+
+    if entries.len() != count {
+        return None;
+    }
+    let commit_point = entries.pop()?;
+
+    Some((entries, commit_point))
+}
+
+fn split_length_prefixed(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    if bytes.len() < 8 {
+        return None;
+    }
+
+    let (length, rest) = bytes.split_at(8);
+    let length = u64::from_le_bytes(length.try_into().ok()?) as usize;
+    if rest.len() < length {
+        return None;
+    }
+
+    Some(rest.split_at(length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_decode_journal() {
+        let entries = vec![JournalEntry::Epoch {
+            name: String::from("epoch_accumulator_2.txt"),
+            content: vec![1, 2, 3],
+        }];
+        let commit_point = JournalEntry::Master {
+            name: String::from("master_accumulator.txt"),
+            content: vec![4; 100],
+        };
+
+        //only the hash of the commit point is journaled
+        let encoded = encode_journal(&entries, &commit_point);
+        let first_entry_length = 8 + 1 + 8 + 23 + 8 + 3;
+        assert_eq!(first_entry_length + 1 + 8 + 22 + 8 + 32, encoded.len());
+        let hashed_commit_point = JournalEntry::Master {
+            name: String::from("master_accumulator.txt"),
+            content: hash(&[4; 100]),
+        };
+        assert_eq!(
+            Some((entries, hashed_commit_point)),
+            decode_journal(&encoded)
+        );
+
+        //a truncated journal is never partially replayed
+        assert_eq!(None, decode_journal(&encoded[..encoded.len() - 1]));
+        assert_eq!(None, decode_journal(&encoded[..first_entry_length]));
+        assert_eq!(None, decode_journal(&0u64.to_le_bytes()));
+    }
+
+    #[test]
+    fn parse_epoch_accumulator_name_of_every_epoch() {
+        assert_eq!(
+            Some(12),
+            parse_epoch_accumulator_name(&get_epoch_accumulator_name(&12))
+        );
+        assert_eq!(None, parse_epoch_accumulator_name("epoch_accumulator_.txt"));
+        assert_eq!(
+            None,
+            parse_epoch_accumulator_name("epoch_accumulator_+1.txt")
+        );
+        assert_eq!(None, parse_epoch_accumulator_name(MASTER_ACCUMULATOR_NAME));
+    }
+}
@@ -1 +1,5 @@
-pub mod accumulator_storage;
\ No newline at end of file
+pub mod accumulator_storage;
+pub mod accumulator_store;
+pub mod manifest;
+pub mod memory_storage;
+pub mod sled_storage;
@@ -0,0 +1,229 @@
+use crate::accumulator_storage::AccumulatorFileStorage;
+use crate::accumulator_store::{parse_epoch_accumulator_name, AccumulatorStore, JOURNAL_NAME};
+use eth2_hashing::hash;
+use std::collections::HashSet;
+use std::path::Path;
+
+pub const MANIFEST_FILE_NAME: &str = "MANIFEST";
+
+//one line per file: sha256, size in bytes, role and file name separated by tabs
+//the role is "master" or "epoch N", it also tells which folder the file is in
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestEntry {
+    pub sha256: String,
+    pub size: u64,
+    pub role: String,
+    pub file_name: String,
+}
+
+impl ManifestEntry {
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.sha256, self.size, self.role, self.file_name
+        )
+    }
+
+    pub fn from_line(line: &str) -> Option<ManifestEntry> {
+        let mut fields = line.split('\t');
+        let entry = ManifestEntry {
+            sha256: String::from(fields.next()?),
+            size: fields.next()?.parse().ok()?,
+            role: String::from(fields.next()?),
+            file_name: String::from(fields.next()?),
+        };
+
+        match fields.next() {
+            Some(_) => None,
+            None => Some(entry),
+        }
+    }
+
+    fn is_epoch(&self) -> bool {
+        self.role.starts_with("epoch")
+    }
+}
+
+impl AccumulatorFileStorage {
+    //the manifest is kept next to the master accumulator and does not list itself
+    pub fn write_manifest(&self) -> bool {
+        let mut content = String::new();
+        for entry in self.get_manifest_entries().iter() {
+            content.push_str(&entry.to_line());
+            content.push('\n');
+        }
+
+        let manifest_path = self.concat_file_path(&self.master_file_path, MANIFEST_FILE_NAME);
+        self.replace_file(&manifest_path, content.as_bytes())
+    }
+
+    //returns the number of verified files, or every mismatch found
+    pub fn verify_manifest(&self) -> Result<usize, Vec<String>> {
+        let manifest_path = self.concat_file_path(&self.master_file_path, MANIFEST_FILE_NAME);
+        let content = std::fs::read_to_string(manifest_path)
+            .map_err(|_| vec![String::from("Unable to read the manifest")])?;
+
+        let mut errors = vec![];
+        let mut listed = HashSet::new();
+        for line in content.lines().filter(|x| !x.is_empty()) {
+            let expected = match ManifestEntry::from_line(line) {
+                Some(x) => x,
+                None => {
+                    errors.push(format!("Invalid manifest line: {}", line));
+                    continue;
+                }
+            };
+            listed.insert((expected.is_epoch(), expected.file_name.clone()));
+
+            let base_path = self.get_manifest_base_path(expected.is_epoch());
+            let file_path = self.concat_file_path(base_path, &expected.file_name);
+            match self.get_manifest_entry(&file_path, &expected.role) {
+                Some(actual) if actual == expected => (),
+                Some(_) => errors.push(format!("{} does not match the manifest", file_path)),
+                None => errors.push(format!("{} is missing", file_path)),
+            }
+        }
+
+        for entry in self.get_manifest_entries().iter() {
+            if !listed.contains(&(entry.is_epoch(), entry.file_name.clone())) {
+                let base_path = self.get_manifest_base_path(entry.is_epoch());
+                let file_path = self.concat_file_path(base_path, &entry.file_name);
+                errors.push(format!("{} is not in the manifest", file_path));
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(listed.len()),
+            false => Err(errors),
+        }
+    }
+
+    fn get_manifest_base_path(&self, is_epoch: bool) -> &str {
+        match is_epoch {
+            true => &self.epoch_file_path,
+            false => &self.master_file_path,
+        }
+    }
+
+    //master files first, then epoch files in epoch order
+    fn get_manifest_entries(&self) -> Vec<ManifestEntry> {
+        let mut entries = vec![];
+        for file_name in self.list_master_accumulators() {
+            //the journal is empty outside of a commit and is not part of the accumulator
+            if file_name == MANIFEST_FILE_NAME || file_name == JOURNAL_NAME {
+                continue;
+            }
+
+            let file_path = self.concat_file_path(&self.master_file_path, &file_name);
+            entries.extend(self.get_manifest_entry(&file_path, "master"));
+        }
+
+        let mut epoch_entries = vec![];
+        for file_name in self.list_epoch_accumulators() {
+            let epoch_number = parse_epoch_accumulator_name(&file_name);
+            let role = match epoch_number {
+                Some(x) => format!("epoch {}", x),
+                None => String::from("epoch"),
+            };
+
+            let file_path = self.concat_file_path(&self.epoch_file_path, &file_name);
+            if let Some(entry) = self.get_manifest_entry(&file_path, &role) {
+                epoch_entries.push((epoch_number, entry));
+            }
+        }
+        epoch_entries.sort_by(|a, b| (a.0, &a.1.file_name).cmp(&(b.0, &b.1.file_name)));
+        entries.extend(epoch_entries.into_iter().map(|x| x.1));
+
+        entries
+    }
+
+    fn get_manifest_entry(&self, file_path: &str, role: &str) -> Option<ManifestEntry> {
+        let content = std::fs::read(file_path).ok()?;
+        let file_name = Path::new(file_path).file_name()?.to_str()?;
+
+        Some(ManifestEntry {
+            sha256: to_hex(&hash(&content)),
+            size: content.len() as u64,
+            role: String::from(role),
+            file_name: String::from(file_name),
+        })
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_PATH: &str = "test_manifest";
+
+    fn init_acculumulator_file_storage() -> AccumulatorFileStorage {
+        let storage = AccumulatorFileStorage::new(
+            format!("{}/master", TEST_PATH),
+            format!("{}/epoch", TEST_PATH),
+        );
+        std::fs::create_dir_all(&storage.master_file_path).unwrap();
+        std::fs::create_dir_all(&storage.epoch_file_path).unwrap();
+
+        std::fs::write("test_manifest/master/master_accumulator.txt", [1; 64]).unwrap();
+        std::fs::write("test_manifest/epoch/epoch_accumulator_2.txt", [2; 128]).unwrap();
+        std::fs::write("test_manifest/epoch/epoch_accumulator_10.txt", [3; 64]).unwrap();
+        //a file without an epoch number is listed before the numbered epochs
+        std::fs::write("test_manifest/epoch/epoch_accumulator.txt", [6; 32]).unwrap();
+
+        storage
+    }
+
+    #[test]
+    fn manifest_entry_line_round_trip() {
+        let entry = ManifestEntry {
+            sha256: String::from("ab"),
+            size: 12,
+            role: String::from("epoch 3"),
+            file_name: String::from("epoch_accumulator_3.txt"),
+        };
+
+        assert_eq!("ab\t12\tepoch 3\tepoch_accumulator_3.txt", entry.to_line());
+        assert_eq!(
+            Some(entry),
+            ManifestEntry::from_line("ab\t12\tepoch 3\tepoch_accumulator_3.txt")
+        );
+        assert_eq!(None, ManifestEntry::from_line("ab\t12\tmaster"));
+    }
+
+    #[test]
+    fn write_and_verify_manifest() {
+        let storage = init_acculumulator_file_storage();
+        assert!(storage.write_manifest());
+
+        let manifest = std::fs::read_to_string("test_manifest/master/MANIFEST").unwrap();
+        let lines: Vec<&str> = manifest.lines().collect();
+        assert_eq!(4, lines.len());
+        assert!(lines[0].ends_with("\t64\tmaster\tmaster_accumulator.txt"));
+        assert!(lines[1].ends_with("\t32\tepoch\tepoch_accumulator.txt"));
+        assert!(lines[2].ends_with("\t128\tepoch 2\tepoch_accumulator_2.txt"));
+        assert!(lines[3].ends_with("\t64\tepoch 10\tepoch_accumulator_10.txt"));
+        assert!(!Path::new("test_manifest/master/MANIFEST.tmp").exists());
+        assert_eq!(Ok(4), storage.verify_manifest());
+
+        //same size, different content
+        std::fs::write("test_manifest/epoch/epoch_accumulator_2.txt", [4; 128]).unwrap();
+        std::fs::write("test_manifest/epoch/epoch_accumulator_11.txt", [5; 64]).unwrap();
+        std::fs::remove_file("test_manifest/master/master_accumulator.txt").unwrap();
+
+        let errors = storage.verify_manifest().unwrap_err();
+        assert_eq!(
+            vec![
+                "test_manifest/master/master_accumulator.txt is missing",
+                "test_manifest/epoch/epoch_accumulator_2.txt does not match the manifest",
+                "test_manifest/epoch/epoch_accumulator_11.txt is not in the manifest",
+            ],
+            errors
+        );
+
+        std::fs::remove_dir_all(TEST_PATH).unwrap();
+    }
+}
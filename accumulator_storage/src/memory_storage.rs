@@ -0,0 +1,171 @@
+use crate::accumulator_store::AccumulatorStore;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+type Blobs = RwLock<HashMap<String, Vec<u8>>>;
+
+//keeps the blobs in memory with the same create before write rule as the file storage
+//nothing is persisted, meant for tests and for tools that embed the accumulator
+#[derive(Debug, Default)]
+pub struct AccumulatorMemoryStorage {
+    master_accumulators: Blobs,
+    epoch_accumulators: Blobs,
+}
+
+impl AccumulatorMemoryStorage {
+    pub fn new() -> AccumulatorMemoryStorage {
+        AccumulatorMemoryStorage::default()
+    }
+
+    fn get_blob(&self, blobs: &Blobs, name: &str) -> Result<Vec<u8>, String> {
+        Ok(blobs.read().unwrap().get(name).cloned().unwrap_or_default())
+    }
+
+    fn write_blob(&self, blobs: &Blobs, name: &str, content: &[u8]) -> bool {
+        match blobs.write().unwrap().get_mut(name) {
+            Some(x) => {
+                *x = content.to_vec();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn create_blob(&self, blobs: &Blobs, name: &str) -> bool {
+        let mut blobs = blobs.write().unwrap();
+        if blobs.contains_key(name) {
+            return false;
+        }
+
+        blobs.insert(String::from(name), vec![]);
+        true
+    }
+
+    //sorted like the file names of the file storage
+    fn list_blob_names(&self, blobs: &Blobs) -> Vec<String> {
+        let mut names: Vec<String> = blobs.read().unwrap().keys().cloned().collect();
+        names.sort();
+
+        names
+    }
+}
+
+impl AccumulatorStore for AccumulatorMemoryStorage {
+    fn get_master_accumulator(&self, name: &str) -> Result<Vec<u8>, String> {
+        self.get_blob(&self.master_accumulators, name)
+    }
+
+    fn get_epoch_accumulator(&self, name: &str) -> Result<Vec<u8>, String> {
+        self.get_blob(&self.epoch_accumulators, name)
+    }
+
+    fn write_master_accumulator(&self, name: &str, content: &[u8]) -> bool {
+        self.write_blob(&self.master_accumulators, name, content)
+    }
+
+    fn write_epoch_accumulator(&self, name: &str, content: &[u8]) -> bool {
+        self.write_blob(&self.epoch_accumulators, name, content)
+    }
+
+    fn append_master_accumulator(&self, name: &str, content: &[u8]) -> bool {
+        match self.master_accumulators.write().unwrap().get_mut(name) {
+            Some(x) => {
+                x.extend_from_slice(content);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn create_new_master_accumulator_file(&self, name: &str) -> bool {
+        self.create_blob(&self.master_accumulators, name)
+    }
+
+    fn create_new_epoch_accumulator_file(&self, name: &str) -> bool {
+        self.create_blob(&self.epoch_accumulators, name)
+    }
+
+    fn list_master_accumulators(&self) -> Vec<String> {
+        self.list_blob_names(&self.master_accumulators)
+    }
+
+    fn list_epoch_accumulators(&self) -> Vec<String> {
+        self.list_blob_names(&self.epoch_accumulators)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accumulator_store::JournalEntry;
+
+    #[test]
+    fn write_fails_before_blob_is_created() {
+        let storage = AccumulatorMemoryStorage::new();
+        let name = "master_accumulator.txt";
+
+        assert!(storage.get_master_accumulator(name).unwrap().is_empty());
+        assert!(!storage.write_master_accumulator(name, &[1]));
+        assert!(!storage.append_master_accumulator(name, &[1]));
+
+        assert!(storage.create_new_master_accumulator_file(name));
+        assert!(!storage.create_new_master_accumulator_file(name));
+        assert!(storage.write_master_accumulator(name, &[1, 2]));
+        assert!(storage.append_master_accumulator(name, &[3]));
+        assert_eq!(vec![1, 2, 3], storage.get_master_accumulator(name).unwrap());
+    }
+
+    #[test]
+    fn master_and_epoch_blobs_are_kept_apart() {
+        let storage = AccumulatorMemoryStorage::new();
+        storage.create_new_epoch_accumulator_file("epoch_accumulator_2.txt");
+        storage.create_new_epoch_accumulator_file("epoch_accumulator_1.txt");
+        storage.write_epoch_accumulator("epoch_accumulator_1.txt", &[4]);
+
+        assert!(storage
+            .get_master_accumulator("epoch_accumulator_1.txt")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            vec![4],
+            storage
+                .get_epoch_accumulator("epoch_accumulator_1.txt")
+                .unwrap()
+        );
+        assert!(storage.list_master_accumulators().is_empty());
+        assert_eq!(
+            vec!["epoch_accumulator_1.txt", "epoch_accumulator_2.txt"],
+            storage.list_epoch_accumulators()
+        );
+    }
+
+    #[test]
+    fn commit_writes_every_entry() {
+        let storage = AccumulatorMemoryStorage::new();
+        let entries = vec![
+            JournalEntry::Epoch {
+                name: String::from("epoch_accumulator_1.txt"),
+                content: vec![1],
+            },
+            JournalEntry::Master {
+                name: String::from("master_accumulator.txt"),
+                content: vec![2],
+            },
+        ];
+
+        assert!(storage.commit(&entries));
+        assert_eq!(
+            vec![1],
+            storage
+                .get_epoch_accumulator("epoch_accumulator_1.txt")
+                .unwrap()
+        );
+        assert_eq!(
+            vec![2],
+            storage
+                .get_master_accumulator("master_accumulator.txt")
+                .unwrap()
+        );
+        assert!(storage.recover());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::accumulator_store::{
+    get_epoch_accumulator_name, parse_epoch_accumulator_name, AccumulatorStore, JournalEntry,
+    MASTER_ACCUMULATOR_NAME, MASTER_ROOT_HISTORY_NAME, MASTER_ROOT_NAME,
+};
+use sled::transaction::{ConflictableTransactionError, TransactionResult};
+use sled::{Db, Transactional, Tree};
+use std::convert::TryInto;
+
+const MASTER_TREE_NAME: &str = "master_accumulators";
+const EPOCH_TREE_NAME: &str = "epoch_accumulators";
+
+//the blobs of the master tree have fixed keys, any other name is refused
+const MASTER_KEYS: [(&str, &[u8]); 3] = [
+    (MASTER_ACCUMULATOR_NAME, b"master"),
+    (MASTER_ROOT_NAME, b"root"),
+    (MASTER_ROOT_HISTORY_NAME, b"root_history"),
+];
+
+//master and epoch accumulators are kept in two trees of one database
+//epochs are keyed by their epoch number as a big endian u64, so the tree is in epoch order
+//a blob has to be created before it can be written, like the files of the file storage
+#[derive(Debug)]
+pub struct AccumulatorSledStorage {
+    db: Db,
+    master_accumulators: Tree,
+    epoch_accumulators: Tree,
+}
+
+impl AccumulatorSledStorage {
+    //creates the database directory if it does not exist
+    pub fn open(db_path: &str) -> Result<AccumulatorSledStorage, String> {
+        let db = sled::open(db_path).map_err(|x| x.to_string())?;
+        AccumulatorSledStorage::from_db(db)
+    }
+
+    fn from_db(db: Db) -> Result<AccumulatorSledStorage, String> {
+        let master_accumulators = db.open_tree(MASTER_TREE_NAME).map_err(|x| x.to_string())?;
+        let epoch_accumulators = db.open_tree(EPOCH_TREE_NAME).map_err(|x| x.to_string())?;
+
+        Ok(AccumulatorSledStorage {
+            db,
+            master_accumulators,
+            epoch_accumulators,
+        })
+    }
+
+    //sled only guarantees what was flushed, so every change is flushed before returning
+    fn flush(&self) -> bool {
+        self.db.flush().is_ok()
+    }
+
+    //a name without a key can never be stored, so it reads as empty like a missing blob
+    fn get_blob(&self, tree: &Tree, key: Option<Vec<u8>>) -> Result<Vec<u8>, String> {
+        match key.map(|x| tree.get(x)) {
+            Some(Ok(Some(x))) => Ok(x.to_vec()),
+            Some(Err(x)) => Err(x.to_string()),
+            _ => Ok(vec![]),
+        }
+    }
+
+    //a blob that was not created is left missing
+    fn update_blob(&self, tree: &Tree, key: Option<Vec<u8>>, f: impl Fn(&[u8]) -> Vec<u8>) -> bool {
+        let key = match key {
+            Some(x) => x,
+            None => return false,
+        };
+
+        let result = tree.update_and_fetch(key, |old| old.map(&f));
+        matches!(result, Ok(Some(_))) && self.flush()
+    }
+
+    fn create_blob(&self, tree: &Tree, key: Option<Vec<u8>>) -> bool {
+        let key = match key {
+            Some(x) => x,
+            None => return false,
+        };
+
+        let empty_blob: &[u8] = &[];
+        let result = tree.compare_and_swap(key, None as Option<&[u8]>, Some(empty_blob));
+        matches!(result, Ok(Ok(()))) && self.flush()
+    }
+
+    fn list_blob_names(&self, tree: &Tree, get_name: fn(&[u8]) -> Option<String>) -> Vec<String> {
+        tree.iter()
+            .keys()
+            .filter_map(|x| x.ok())
+            .filter_map(|x| get_name(&x))
+            .collect()
+    }
+}
+
+fn get_master_key(name: &str) -> Option<Vec<u8>> {
+    MASTER_KEYS
+        .iter()
+        .find(|x| x.0 == name)
+        .map(|x| x.1.to_vec())
+}
+
+fn get_master_name(key: &[u8]) -> Option<String> {
+    MASTER_KEYS
+        .iter()
+        .find(|x| x.1 == key)
+        .map(|x| String::from(x.0))
+}
+
+fn get_epoch_key(name: &str) -> Option<Vec<u8>> {
+    parse_epoch_accumulator_name(name).map(|x| x.to_be_bytes().to_vec())
+}
+
+fn get_epoch_name(key: &[u8]) -> Option<String> {
+    let epoch_number = u64::from_be_bytes(key.try_into().ok()?);
+    Some(get_epoch_accumulator_name(&(epoch_number as usize)))
+}
+
+impl AccumulatorStore for AccumulatorSledStorage {
+    fn get_master_accumulator(&self, name: &str) -> Result<Vec<u8>, String> {
+        self.get_blob(&self.master_accumulators, get_master_key(name))
+    }
+
+    fn get_epoch_accumulator(&self, name: &str) -> Result<Vec<u8>, String> {
+        self.get_blob(&self.epoch_accumulators, get_epoch_key(name))
+    }
+
+    fn write_master_accumulator(&self, name: &str, content: &[u8]) -> bool {
+        self.update_blob(&self.master_accumulators, get_master_key(name), |_| {
+            content.to_vec()
+        })
+    }
+
+    fn write_epoch_accumulator(&self, name: &str, content: &[u8]) -> bool {
+        self.update_blob(&self.epoch_accumulators, get_epoch_key(name), |_| {
+            content.to_vec()
+        })
+    }
+
+    fn append_master_accumulator(&self, name: &str, content: &[u8]) -> bool {
+        self.update_blob(&self.master_accumulators, get_master_key(name), |old| {
+            [old, content].concat()
+        })
+    }
+
+    fn create_new_master_accumulator_file(&self, name: &str) -> bool {
+        self.create_blob(&self.master_accumulators, get_master_key(name))
+    }
+
+    fn create_new_epoch_accumulator_file(&self, name: &str) -> bool {
+        self.create_blob(&self.epoch_accumulators, get_epoch_key(name))
+    }
+
+    fn list_master_accumulators(&self) -> Vec<String> {
+        self.list_blob_names(&self.master_accumulators, get_master_name)
+    }
+
+    fn list_epoch_accumulators(&self) -> Vec<String> {
+        self.list_blob_names(&self.epoch_accumulators, get_epoch_name)
+    }
+
+    //one transaction over both trees takes the place of the journal, so recover never has work to do
+    //blobs are inserted without being created first, since replaying the journal of the
+    //other storages also creates every blob it writes
+    fn commit(&self, entries: &[JournalEntry]) -> bool {
+        let result: TransactionResult<()> = (&self.master_accumulators, &self.epoch_accumulators)
+            .transaction(|(master_accumulators, epoch_accumulators)| {
+                for entry in entries.iter() {
+                    let (tree, key, content) = match entry {
+                        JournalEntry::Master { name, content } => {
+                            (master_accumulators, get_master_key(name), content)
+                        }
+                        JournalEntry::Epoch { name, content } => {
+                            (epoch_accumulators, get_epoch_key(name), content)
+                        }
+                    };
+
+                    //a name without a key aborts the transaction, so none of the entries are written
+                    let key = key.ok_or(ConflictableTransactionError::Abort(()))?;
+                    tree.insert(key, &content[..])?;
+                }
+
+                Ok(())
+            });
+
+        result.is_ok() && self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //a temporary database is removed when the storage is dropped, even if the test fails
+    fn open_storage() -> AccumulatorSledStorage {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        AccumulatorSledStorage::from_db(db).unwrap()
+    }
+
+    #[test]
+    fn write_fails_before_blob_is_created() {
+        let storage = open_storage();
+        let name = MASTER_ACCUMULATOR_NAME;
+
+        assert!(storage.get_master_accumulator(name).unwrap().is_empty());
+        assert!(!storage.write_master_accumulator(name, &[1]));
+        assert!(!storage.append_master_accumulator(name, &[1]));
+        assert!(storage.list_master_accumulators().is_empty());
+
+        assert!(storage.create_new_master_accumulator_file(name));
+        assert!(!storage.create_new_master_accumulator_file(name));
+        assert!(storage.write_master_accumulator(name, &[1, 2]));
+        assert!(storage.append_master_accumulator(name, &[3]));
+        assert_eq!(vec![1, 2, 3], storage.get_master_accumulator(name).unwrap());
+    }
+
+    #[test]
+    fn commit_writes_both_trees() {
+        let storage = open_storage();
+        let entries = vec![
+            JournalEntry::Epoch {
+                name: String::from("epoch_accumulator_1.txt"),
+                content: vec![1],
+            },
+            JournalEntry::Master {
+                name: String::from(MASTER_ACCUMULATOR_NAME),
+                content: vec![2],
+            },
+        ];
+
+        assert!(storage.commit(&entries));
+        assert!(storage.recover());
+        assert_eq!(
+            vec![1],
+            storage
+                .get_epoch_accumulator("epoch_accumulator_1.txt")
+                .unwrap()
+        );
+        assert_eq!(
+            vec![2],
+            storage
+                .get_master_accumulator(MASTER_ACCUMULATOR_NAME)
+                .unwrap()
+        );
+        assert_eq!(
+            vec![String::from(MASTER_ACCUMULATOR_NAME)],
+            storage.list_master_accumulators()
+        );
+        assert!(storage.master_accumulators.contains_key(b"master").unwrap());
+    }
+
+    #[test]
+    fn epochs_are_keyed_and_listed_by_epoch_number() {
+        let storage = open_storage();
+        for epoch_number in [10, 2, 1].iter() {
+            let name = get_epoch_accumulator_name(epoch_number);
+            assert!(storage.create_new_epoch_accumulator_file(&name));
+        }
+
+        assert_eq!(
+            vec![
+                "epoch_accumulator_1.txt",
+                "epoch_accumulator_2.txt",
+                "epoch_accumulator_10.txt"
+            ],
+            storage.list_epoch_accumulators()
+        );
+        assert!(storage
+            .epoch_accumulators
+            .contains_key(10u64.to_be_bytes())
+            .unwrap());
+    }
+
+    #[test]
+    fn unknown_names_are_refused() {
+        let storage = open_storage();
+        let entries = vec![
+            JournalEntry::Epoch {
+                name: String::from("epoch_accumulator_1.txt"),
+                content: vec![1],
+            },
+            JournalEntry::Master {
+                name: String::from("unknown.txt"),
+                content: vec![2],
+            },
+        ];
+
+        assert!(!storage.create_new_master_accumulator_file("unknown.txt"));
+        assert!(!storage.create_new_epoch_accumulator_file("epoch_accumulator_x.txt"));
+        assert!(!storage.commit(&entries));
+        assert!(storage.list_master_accumulators().is_empty());
+        assert!(storage.list_epoch_accumulators().is_empty());
+    }
+}
@@ -1,8 +1,11 @@
+use serde_derive::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use tree_hash_derive::TreeHash;
 use ethereum_types::{H256, U256};
 
-#[derive(Clone, Copy, Encode, Decode, Debug, PartialEq, TreeHash, Default)]
+#[derive(
+    Clone, Copy, Encode, Decode, Debug, PartialEq, TreeHash, Default, Serialize, Deserialize,
+)]
 pub struct EpochSede {
     block_hash: H256,
     total_difficulty: U256,
@@ -15,4 +18,12 @@ impl EpochSede {
             total_difficulty,
         }
     }
+
+    pub fn block_hash(&self) -> H256 {
+        self.block_hash
+    }
+
+    pub fn total_difficulty(&self) -> U256 {
+        self.total_difficulty
+    }
 }
\ No newline at end of file
@@ -1,7 +1,14 @@
 pub mod epoch_sedes;
+pub mod master_accumulator;
+pub mod merkle_tree;
+pub mod portal;
+pub mod proof;
 pub mod trie;
 
 use epoch_sedes::EpochSede;
+use master_accumulator::MasterAccumulator;
+use merkle_tree::{get_epoch_tree, MerkleTree, MASTER_TREE_DEPTH};
+use proof::EpochMultiproof;
 use trie::TrieTrait;
 
 use ssz::{Decode, DecodeError, Encode};
@@ -9,6 +16,8 @@ use ssz_types::{typenum, VariableList};
 use tree_hash::TreeHash;
 use typenum::{U16777216, U2048};
 use ethereum_types::{H256};
+use std::cell::RefCell;
+use std::str::FromStr;
 
 pub trait AccumulatorTrieTrait<T>
 where
@@ -17,6 +26,21 @@ where
     fn new(master_trie: T) -> Self;
     fn get_master_accumulator(&self) -> Result<Vec<H256>, DecodeError>;
     fn store_master_accumulator(&self, master_accumulator: &Vec<H256>) -> bool;
+    fn store_epoch_and_master_accumulator(
+        &self,
+        epoch_number: &usize,
+        epoch_accumulator: &Vec<EpochSede>,
+        master_accumulator: &[H256],
+    ) -> Option<H256>;
+    fn recover_accumulators(&self) -> bool;
+    fn get_master_accumulator_root(&self) -> Option<H256>;
+    fn get_master_root_hash(&self, master_accumulator: &[H256]) -> H256;
+    fn store_historical_master_roots(&self, completed_master_accumulator: &[H256]) -> bool;
+    fn get_master_root_by_epoch_number(&self, epoch_number: &usize) -> Option<H256>;
+    fn get_master_root_by_block_number(&self, block_number: &u32) -> Option<H256>;
+    fn get_current_epoch_accumulator(&self, master_accumulator: &[H256]) -> Option<Vec<EpochSede>>;
+    fn export_master_accumulator(&self) -> Option<MasterAccumulator>;
+    fn import_master_accumulator(&self, master_accumulator: &MasterAccumulator) -> bool;
     fn get_epoch_accumulator_by_epoch_number(
         &self,
         epoch_number: &usize,
@@ -28,11 +52,22 @@ where
     ) -> bool;
 
     fn get_tree_root_hash(&self, epoch_accumulator: &Vec<EpochSede>) -> H256;
+    fn get_epoch_number_and_index(&self, block_number: &u32) -> (usize, usize);
+    fn get_epoch_sede_by_block_number(&self, block_number: &u32) -> Option<EpochSede>;
+    fn generate_block_proof(&self, block_number: &u32) -> Option<Vec<H256>>;
+    fn generate_epoch_proof(&self, epoch_number: &usize) -> Option<Vec<H256>>;
+    fn generate_epoch_multiproof(
+        &self,
+        epoch_number: &usize,
+        indices: &[usize],
+    ) -> Option<EpochMultiproof>;
 }
 
 pub struct AccumulatorTrie<T: TrieTrait> {
     pub trie: T,
     pub epoch_size: u32,
+    //cached so that changing the last master entry only rehashes its path
+    master_tree: RefCell<MerkleTree>,
 }
 
 impl<T> AccumulatorTrieTrait<T> for AccumulatorTrie<T>
@@ -43,11 +78,16 @@ where
         AccumulatorTrie {
             trie,
             epoch_size: 2048,
+            master_tree: RefCell::new(MerkleTree::new(vec![], MASTER_TREE_DEPTH)),
         }
     }
 
     fn get_master_accumulator(&self) -> Result<Vec<H256>, DecodeError> {
-        let encoded_ssz = self.trie.get_master_accumulator_encoded();
+        //a file that cannot be read is reported like one that cannot be decoded
+        let encoded_ssz = self
+            .trie
+            .get_master_accumulator_encoded()
+            .map_err(DecodeError::BytesInvalid)?;
         let decoded = <VariableList<H256, U16777216>>::from_ssz_bytes(&encoded_ssz);
 
         match decoded {
@@ -61,11 +101,194 @@ where
             VariableList::from(master_accumulator.clone());
 
         let encoded_master_sede_var_list = master_sede_var_list.as_ssz_bytes();
-        let result = self
+
+        //the root is kept as hex so it can be compared by hand
+        //both are written in one commit so the root never belongs to another master accumulator
+        let master_root = format!("{:?}", self.get_master_root_hash(master_accumulator));
+        self.trie.commit_master_accumulator_encoded(
+            &encoded_master_sede_var_list,
+            &master_root.into_bytes(),
+        )
+    }
+
+    //commits both accumulators and the master root together, see recover_accumulators
+    //returns the master root that was stored, none if the commit failed
+    fn store_epoch_and_master_accumulator(
+        &self,
+        epoch_number: &usize,
+        epoch_accumulator: &Vec<EpochSede>,
+        master_accumulator: &[H256],
+    ) -> Option<H256> {
+        let master_root = self.get_master_root_hash(master_accumulator);
+
+        //a vec encodes to the same bytes as the ssz list, so the epoch accumulator is not copied
+        //the ssz list of the fixed size master roots is the roots one after another
+        let mut master_encoded = Vec::with_capacity(master_accumulator.len() * H256::len_bytes());
+        master_accumulator
+            .iter()
+            .for_each(|x| x.ssz_append(&mut master_encoded));
+
+        let result = self.trie.commit_accumulators_encoded(
+            epoch_number,
+            &epoch_accumulator.as_ssz_bytes(),
+            &master_encoded,
+            &format!("{:?}", master_root).into_bytes(),
+        );
+
+        match result {
+            true => Some(master_root),
+            false => None,
+        }
+    }
+
+    //finishes a store_epoch_and_master_accumulator that was interrupted, call before reading
+    fn recover_accumulators(&self) -> bool {
+        self.trie.recover_accumulators()
+    }
+
+    //the root persisted by the last store_master_accumulator
+    fn get_master_accumulator_root(&self) -> Option<H256> {
+        let encoded_root = self.trie.get_master_accumulator_root_encoded().ok()?;
+        let root = std::str::from_utf8(&encoded_root).ok()?;
+
+        H256::from_str(root.trim().trim_start_matches("0x")).ok()
+    }
+
+    fn get_master_root_hash(&self, master_accumulator: &[H256]) -> H256 {
+        let mut master_tree = self.master_tree.borrow_mut();
+        master_tree.update_leaves(master_accumulator);
+
+        master_tree.hash_tree_root()
+    }
+
+    //records the master root as it stood at the end of every completed epoch
+    //entry N - 1 of the history is the root of the first N entries of the master accumulator
+    //not journaled, the history is derived from the master accumulator and checked against it on every call
+    fn store_historical_master_roots(&self, completed_master_accumulator: &[H256]) -> bool {
+        //an unreadable history is left as it is rather than rewritten
+        let root_history = match self.trie.get_master_accumulator_root_history_encoded() {
+            Ok(x) => x,
+            Err(_) => return false,
+        };
+
+        let mut master_tree = MerkleTree::new(vec![], MASTER_TREE_DEPTH);
+        let mut master_roots = vec![];
+        for epoch_hash in completed_master_accumulator.iter() {
+            master_tree.push(*epoch_hash);
+            master_roots.push(master_tree.hash_tree_root());
+        }
+
+        //already recorded epochs are checked instead of appended again, so this is safe to repeat
+        let recorded_roots: Vec<H256> = root_history
+            .chunks_exact(H256::len_bytes())
+            .map(H256::from_slice)
+            .collect();
+        let recorded_epochs = recorded_roots.len().min(master_roots.len());
+        let is_consistent = root_history.len().is_multiple_of(H256::len_bytes())
+            && recorded_roots[..recorded_epochs] == master_roots[..recorded_epochs];
+
+        if is_consistent {
+            let new_roots: Vec<u8> = master_roots[recorded_epochs..]
+                .iter()
+                .flat_map(|x| x.as_bytes().to_vec())
+                .collect();
+            if new_roots.is_empty() {
+                return true;
+            }
+
+            return self
+                .trie
+                .append_master_accumulator_root_history_encoded(&new_roots);
+        }
+
+        //a torn append leaves a partial entry behind that would misalign every later entry
+        //the history only depends on the master accumulator, so it is written again from it
+        let root_history: Vec<u8> = master_roots
+            .iter()
+            .flat_map(|x| x.as_bytes().to_vec())
+            .collect();
+        self.trie
+            .update_master_accumulator_root_history_encoded(&root_history)
+    }
+
+    fn get_master_root_by_epoch_number(&self, epoch_number: &usize) -> Option<H256> {
+        if *epoch_number == 0 {
+            return None;
+        }
+
+        let root_history = self
             .trie
-            .update_master_accumulator_encoded(&encoded_master_sede_var_list);
+            .get_master_accumulator_root_history_encoded()
+            .ok()?;
+        let start = (epoch_number - 1) * H256::len_bytes();
+        let encoded_root = root_history.get(start..start + H256::len_bytes())?;
 
-        result
+        Some(H256::from_slice(encoded_root))
+    }
+
+    //the master root at the end of the epoch the block belongs to
+    fn get_master_root_by_block_number(&self, block_number: &u32) -> Option<H256> {
+        let (epoch_number, _) = self.get_epoch_number_and_index(block_number);
+        self.get_master_root_by_epoch_number(&epoch_number)
+    }
+
+    //the epoch a run resumes from, none if it cannot be read or its root is not the last master entry
+    fn get_current_epoch_accumulator(&self, master_accumulator: &[H256]) -> Option<Vec<EpochSede>> {
+        let epoch_number = master_accumulator.len();
+        if epoch_number == 0 {
+            return Some(vec![]);
+        }
+
+        let current_epoch = self
+            .get_epoch_accumulator_by_epoch_number(&epoch_number)
+            .ok()?;
+        match self.get_tree_root_hash(&current_epoch) == master_accumulator[epoch_number - 1] {
+            true => Some(current_epoch),
+            false => None,
+        }
+    }
+
+    //the last epoch of the master accumulator is the current epoch until it is full
+    fn export_master_accumulator(&self) -> Option<MasterAccumulator> {
+        let mut historical_epochs = self.get_master_accumulator().ok()?;
+        let epoch_number = historical_epochs.len();
+        if epoch_number == 0 {
+            return Some(MasterAccumulator::new(vec![], vec![]));
+        }
+
+        let current_epoch = self
+            .get_epoch_accumulator_by_epoch_number(&epoch_number)
+            .ok()?;
+        if current_epoch.len() as u32 >= self.epoch_size {
+            return Some(MasterAccumulator::new(historical_epochs, vec![]));
+        }
+
+        historical_epochs.pop();
+        Some(MasterAccumulator::new(historical_epochs, current_epoch))
+    }
+
+    //only the current epoch can be restored as an epoch accumulator
+    //completed epochs are known by their hash alone
+    fn import_master_accumulator(&self, master_accumulator: &MasterAccumulator) -> bool {
+        //refuse to mix the import with an existing accumulator
+        match self.get_master_accumulator() {
+            Ok(x) if x.is_empty() => (),
+            _ => return false,
+        }
+
+        let historical_epochs = master_accumulator.historical_epochs.to_vec();
+        let mut result = self.store_historical_master_roots(&historical_epochs);
+
+        let mut master = historical_epochs;
+        if !master_accumulator.current_epoch.is_empty() {
+            let current_epoch = master_accumulator.current_epoch.to_vec();
+            master.push(self.get_tree_root_hash(&current_epoch));
+
+            result = result
+                && self.store_epoch_accumulator_by_epoch_number(&master.len(), &current_epoch);
+        }
+
+        result && self.store_master_accumulator(&master)
     }
 
     //get and store functions for epoch_accumulator
@@ -73,7 +296,10 @@ where
         &self,
         epoch_number: &usize,
     ) -> Result<Vec<EpochSede>, DecodeError> {
-        let encoded_epoch = self.trie.get_epoch_accumulator_encoded(epoch_number);
+        let encoded_epoch = self
+            .trie
+            .get_epoch_accumulator_encoded(epoch_number)
+            .map_err(DecodeError::BytesInvalid)?;
         let epoch_sede_var_list_decoded =
             <VariableList<EpochSede, U2048>>::from_ssz_bytes(&encoded_epoch);
 
@@ -103,87 +329,491 @@ where
             VariableList::from(epoch_accumulator.clone());
         epoch_sede_fixed_vec.tree_hash_root()
     }
+
+    //block numbers are counted from the first block of epoch 1
+    fn get_epoch_number_and_index(&self, block_number: &u32) -> (usize, usize) {
+        let epoch_number = (block_number / self.epoch_size + 1) as usize;
+        let leaf_index = (block_number % self.epoch_size) as usize;
+
+        (epoch_number, leaf_index)
+    }
+
+    fn get_epoch_sede_by_block_number(&self, block_number: &u32) -> Option<EpochSede> {
+        let (epoch_number, leaf_index) = self.get_epoch_number_and_index(block_number);
+
+        //avoid creating an empty file for an epoch that does not exist yet
+        let master_accumulator = self.get_master_accumulator().ok()?;
+        if epoch_number > master_accumulator.len() {
+            return None;
+        }
+
+        let epoch_accumulator = self
+            .get_epoch_accumulator_by_epoch_number(&epoch_number)
+            .ok()?;
+
+        epoch_accumulator.get(leaf_index).cloned()
+    }
+
+    //branch of the block's epoch_sede in its epoch accumulator, length mix-in included
+    fn generate_block_proof(&self, block_number: &u32) -> Option<Vec<H256>> {
+        let (epoch_number, leaf_index) = self.get_epoch_number_and_index(block_number);
+
+        //avoid creating an empty file for an epoch that does not exist yet
+        let master_accumulator = self.get_master_accumulator().ok()?;
+        if epoch_number > master_accumulator.len() {
+            return None;
+        }
+
+        let epoch_accumulator = self
+            .get_epoch_accumulator_by_epoch_number(&epoch_number)
+            .ok()?;
+        if leaf_index >= epoch_accumulator.len() {
+            return None;
+        }
+
+        let epoch_tree = get_epoch_tree(&epoch_accumulator);
+
+        Some(epoch_tree.branch(leaf_index))
+    }
+
+    //branch of the epoch hash in the master accumulator, length mix-in included
+    //epoch N is stored at index N - 1 of the master accumulator
+    fn generate_epoch_proof(&self, epoch_number: &usize) -> Option<Vec<H256>> {
+        let master_accumulator = self.get_master_accumulator().ok()?;
+        if *epoch_number == 0 || *epoch_number > master_accumulator.len() {
+            return None;
+        }
+
+        let master_tree = MerkleTree::new(master_accumulator, MASTER_TREE_DEPTH);
+
+        Some(master_tree.branch(epoch_number - 1))
+    }
+
+    //one proof for many blocks of the same epoch, indices are positions in the epoch accumulator
+    fn generate_epoch_multiproof(
+        &self,
+        epoch_number: &usize,
+        indices: &[usize],
+    ) -> Option<EpochMultiproof> {
+        let master_accumulator = self.get_master_accumulator().ok()?;
+        if *epoch_number == 0 || *epoch_number > master_accumulator.len() {
+            return None;
+        }
+
+        let epoch_accumulator = self
+            .get_epoch_accumulator_by_epoch_number(epoch_number)
+            .ok()?;
+
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.is_empty() || indices.iter().any(|x| *x >= epoch_accumulator.len()) {
+            return None;
+        }
+
+        let epoch_tree = get_epoch_tree(&epoch_accumulator);
+
+        Some(EpochMultiproof {
+            leaves: indices.iter().map(|x| epoch_accumulator[*x]).collect(),
+            helpers: epoch_tree.multiproof(&indices),
+            indices,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use accumulator_storage::accumulator_storage::AccumulatorFileStorage;
-    use std::cell::RefCell;
-    use std::collections::HashMap;
+    use accumulator_storage::accumulator_store::{AccumulatorStore, JOURNAL_NAME};
+    use merkle_tree::EPOCH_TREE_DEPTH;
+    use accumulator_storage::memory_storage::AccumulatorMemoryStorage;
+    use std::cell::Cell;
     use tree_hash::TreeHash;
+    use trie::Trie;
     use ethereum_types::U256;
 
-    struct MockTrie {
-        encoded_master_accumulator: RefCell<Vec<u8>>,
-        encoded_epoch_accumulator: RefCell<HashMap<String, Vec<u8>>>,
+    //fails every write once writes_until_crash more have succeeded, to simulate a crash
+    struct CrashingStore {
+        store: AccumulatorMemoryStorage,
+        writes_until_crash: Cell<Option<usize>>,
     }
 
-    impl TrieTrait for MockTrie {
-        fn new(_accumulator_storage: AccumulatorFileStorage) -> MockTrie {
-            MockTrie {
-                encoded_master_accumulator: RefCell::new(vec![]),
-                encoded_epoch_accumulator: RefCell::new(HashMap::new()),
+    impl CrashingStore {
+        fn is_crashed(&self) -> bool {
+            match self.writes_until_crash.get() {
+                Some(0) => true,
+                Some(x) => {
+                    self.writes_until_crash.set(Some(x - 1));
+                    false
+                }
+                None => false,
             }
         }
+    }
+
+    impl AccumulatorStore for CrashingStore {
+        fn get_master_accumulator(&self, name: &str) -> Result<Vec<u8>, String> {
+            self.store.get_master_accumulator(name)
+        }
 
-        fn get_master_accumulator_encoded(&self) -> Vec<u8> {
-            self.encoded_master_accumulator.borrow().to_vec()
+        fn get_epoch_accumulator(&self, name: &str) -> Result<Vec<u8>, String> {
+            self.store.get_epoch_accumulator(name)
         }
 
-        fn update_master_accumulator_encoded(&self, trie_encoded: &Vec<u8>) -> bool {
-            let mut mut_tried_encoded = trie_encoded.clone();
+        fn write_master_accumulator(&self, name: &str, content: &[u8]) -> bool {
+            !self.is_crashed() && self.store.write_master_accumulator(name, content)
+        }
 
-            self.encoded_master_accumulator
-                .borrow_mut()
-                .append(&mut mut_tried_encoded);
+        fn write_epoch_accumulator(&self, name: &str, content: &[u8]) -> bool {
+            !self.is_crashed() && self.store.write_epoch_accumulator(name, content)
+        }
 
-            true
+        fn append_master_accumulator(&self, name: &str, content: &[u8]) -> bool {
+            self.store.append_master_accumulator(name, content)
         }
 
-        fn get_file_name_from_epoch_number(&self, epoch_number: &usize) -> String {
-            epoch_number.to_string()
+        fn create_new_master_accumulator_file(&self, name: &str) -> bool {
+            self.store.create_new_master_accumulator_file(name)
         }
 
-        fn get_epoch_accumulator_encoded(&self, epoch_number: &usize) -> Vec<u8> {
-            self.encoded_epoch_accumulator
-                .borrow()
-                .get(&epoch_number.to_string())
-                .unwrap()
-                .to_vec()
+        fn create_new_epoch_accumulator_file(&self, name: &str) -> bool {
+            self.store.create_new_epoch_accumulator_file(name)
         }
 
-        fn update_epoch_accumulator_encoded(
-            &self,
-            epoch_number: &usize,
-            trie_encoded: &Vec<u8>,
-        ) -> bool {
-            self.encoded_epoch_accumulator
-                .borrow_mut()
-                .insert(epoch_number.to_string(), trie_encoded.to_vec());
+        fn list_master_accumulators(&self) -> Vec<String> {
+            self.store.list_master_accumulators()
+        }
 
-            true
+        fn list_epoch_accumulators(&self) -> Vec<String> {
+            self.store.list_epoch_accumulators()
         }
     }
 
+    fn init_crashing_accumulator_trie() -> AccumulatorTrie<Trie<CrashingStore>> {
+        let accumulator_trie = AccumulatorTrie::new(Trie::new(CrashingStore {
+            store: AccumulatorMemoryStorage::new(),
+            writes_until_crash: Cell::new(None),
+        }));
+        accumulator_trie.get_master_accumulator().unwrap();
+
+        accumulator_trie
+    }
+
+    fn init_accumulator_trie() -> AccumulatorTrie<Trie<AccumulatorMemoryStorage>> {
+        let accumulator_trie = AccumulatorTrie::new(Trie::new(AccumulatorMemoryStorage::new()));
+        //creates the master accumulator like the accumulator run does on start
+        accumulator_trie.get_master_accumulator().unwrap();
+
+        accumulator_trie
+    }
+
+    fn get_accumulator_trie_with_epochs(
+        epoch_lengths: &[u64],
+    ) -> AccumulatorTrie<Trie<AccumulatorMemoryStorage>> {
+        let mut accumulator_trie = init_accumulator_trie();
+        accumulator_trie.epoch_size = 4;
+
+        let mut master_accumulator = vec![];
+        for (i, epoch_length) in epoch_lengths.iter().enumerate() {
+            let epoch_number = i + 1;
+            let epoch_accumulator: Vec<EpochSede> = (0..*epoch_length)
+                .map(|x| EpochSede::new(U256::from(x * 10), H256::from_low_u64_be(x + 1)))
+                .collect();
+
+            accumulator_trie
+                .store_epoch_accumulator_by_epoch_number(&epoch_number, &epoch_accumulator);
+            master_accumulator.push(accumulator_trie.get_tree_root_hash(&epoch_accumulator));
+        }
+        accumulator_trie.store_master_accumulator(&master_accumulator);
+
+        accumulator_trie
+    }
+
+    #[test]
+    fn export_and_import_master_accumulator_with_current_epoch() {
+        let accumulator_trie = get_accumulator_trie_with_epochs(&[4, 4, 2]);
+        let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+
+        let exported = accumulator_trie.export_master_accumulator().unwrap();
+        assert_eq!(&master_accumulator[..2], &exported.historical_epochs[..]);
+        assert_eq!(
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&3)
+                .unwrap(),
+            exported.current_epoch.to_vec()
+        );
+
+        let decoded = MasterAccumulator::from_ssz_bytes(&exported.as_ssz_bytes()).unwrap();
+
+        let imported_trie = init_accumulator_trie();
+        assert!(imported_trie.import_master_accumulator(&decoded));
+
+        assert_eq!(
+            master_accumulator,
+            imported_trie.get_master_accumulator().unwrap()
+        );
+        assert_eq!(
+            exported.current_epoch.to_vec(),
+            imported_trie
+                .get_epoch_accumulator_by_epoch_number(&3)
+                .unwrap()
+        );
+        assert_eq!(
+            accumulator_trie.get_master_accumulator_root(),
+            imported_trie.get_master_accumulator_root()
+        );
+        assert_eq!(
+            accumulator_trie.get_master_root_hash(&master_accumulator[..2]),
+            imported_trie.get_master_root_by_epoch_number(&2).unwrap()
+        );
+    }
+
+    #[test]
+    fn export_master_accumulator_with_full_last_epoch() {
+        let accumulator_trie = get_accumulator_trie_with_epochs(&[4, 4]);
+
+        let exported = accumulator_trie.export_master_accumulator().unwrap();
+        assert_eq!(2, exported.historical_epochs.len());
+        assert!(exported.current_epoch.is_empty());
+
+        let imported_trie = init_accumulator_trie();
+        assert!(imported_trie.import_master_accumulator(&exported));
+        assert_eq!(
+            exported.historical_epochs.to_vec(),
+            imported_trie.get_master_accumulator().unwrap()
+        );
+    }
+
+    #[test]
+    fn import_master_accumulator_fails_on_existing_accumulator() {
+        let accumulator_trie = get_accumulator_trie_with_epochs(&[4, 1]);
+        let exported = accumulator_trie.export_master_accumulator().unwrap();
+
+        assert!(!accumulator_trie.import_master_accumulator(&exported));
+    }
+
+    fn get_epoch_and_master_accumulator(length: u64) -> (Vec<EpochSede>, Vec<H256>) {
+        let epoch_accumulator: Vec<EpochSede> = (0..length)
+            .map(|x| EpochSede::new(U256::from(x), H256::from_low_u64_be(x + 1)))
+            .collect();
+        let epoch_var_list: VariableList<_, U2048> = VariableList::from(epoch_accumulator.clone());
+
+        (epoch_accumulator, vec![epoch_var_list.tree_hash_root()])
+    }
+
+    #[test]
+    fn store_epoch_and_master_accumulator_together() {
+        let accumulator_trie = init_accumulator_trie();
+        let (epoch_accumulator, master_accumulator) = get_epoch_and_master_accumulator(3);
+
+        assert_eq!(
+            Some(accumulator_trie.get_master_root_hash(&master_accumulator)),
+            accumulator_trie.store_epoch_and_master_accumulator(
+                &1,
+                &epoch_accumulator,
+                &master_accumulator
+            )
+        );
+        assert_eq!(
+            epoch_accumulator,
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&1)
+                .unwrap()
+        );
+        assert_eq!(
+            master_accumulator,
+            accumulator_trie.get_master_accumulator().unwrap()
+        );
+        assert_eq!(
+            Some(accumulator_trie.get_master_root_hash(&master_accumulator)),
+            accumulator_trie.get_master_accumulator_root()
+        );
+    }
+
+    #[test]
+    fn recover_accumulators_after_crash_between_master_and_root() {
+        let accumulator_trie = init_crashing_accumulator_trie();
+        let (_, master_accumulator) = get_epoch_and_master_accumulator(3);
+        assert!(accumulator_trie.store_master_accumulator(&master_accumulator));
+
+        //the journal and the master are written, the root is not
+        let (_, new_master_accumulator) = get_epoch_and_master_accumulator(4);
+        accumulator_trie
+            .trie
+            .accumulator_storage
+            .writes_until_crash
+            .set(Some(2));
+        assert!(!accumulator_trie.store_master_accumulator(&new_master_accumulator));
+        assert_eq!(
+            Some(accumulator_trie.get_master_root_hash(&master_accumulator)),
+            accumulator_trie.get_master_accumulator_root()
+        );
+
+        accumulator_trie
+            .trie
+            .accumulator_storage
+            .writes_until_crash
+            .set(None);
+        assert!(accumulator_trie.recover_accumulators());
+        assert_eq!(
+            new_master_accumulator,
+            accumulator_trie.get_master_accumulator().unwrap()
+        );
+        assert_eq!(
+            Some(accumulator_trie.get_master_root_hash(&new_master_accumulator)),
+            accumulator_trie.get_master_accumulator_root()
+        );
+    }
+
+    #[test]
+    fn recover_accumulators_after_crash_between_master_and_epoch() {
+        let accumulator_trie = init_crashing_accumulator_trie();
+        let (epoch_accumulator, master_accumulator) = get_epoch_and_master_accumulator(3);
+        accumulator_trie.store_epoch_and_master_accumulator(
+            &1,
+            &epoch_accumulator,
+            &master_accumulator,
+        );
+
+        //the journal and the master are written, the epoch is not
+        let (new_epoch_accumulator, new_master_accumulator) = get_epoch_and_master_accumulator(4);
+        accumulator_trie
+            .trie
+            .accumulator_storage
+            .writes_until_crash
+            .set(Some(2));
+        assert!(accumulator_trie
+            .store_epoch_and_master_accumulator(&1, &new_epoch_accumulator, &new_master_accumulator)
+            .is_none());
+        assert_eq!(
+            epoch_accumulator,
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&1)
+                .unwrap()
+        );
+
+        accumulator_trie
+            .trie
+            .accumulator_storage
+            .writes_until_crash
+            .set(None);
+        assert!(accumulator_trie.recover_accumulators());
+        assert_eq!(
+            new_epoch_accumulator,
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&1)
+                .unwrap()
+        );
+        assert_eq!(
+            new_master_accumulator,
+            accumulator_trie.get_master_accumulator().unwrap()
+        );
+        assert_eq!(
+            Some(accumulator_trie.get_master_root_hash(&new_master_accumulator)),
+            accumulator_trie.get_master_accumulator_root()
+        );
+    }
+
+    #[test]
+    fn recover_accumulators_drops_journal_when_master_is_not_written() {
+        let accumulator_trie = init_crashing_accumulator_trie();
+        let (epoch_accumulator, master_accumulator) = get_epoch_and_master_accumulator(3);
+        accumulator_trie.store_epoch_and_master_accumulator(
+            &1,
+            &epoch_accumulator,
+            &master_accumulator,
+        );
+
+        //only the journal is written
+        let (new_epoch_accumulator, new_master_accumulator) = get_epoch_and_master_accumulator(4);
+        accumulator_trie
+            .trie
+            .accumulator_storage
+            .writes_until_crash
+            .set(Some(1));
+        assert!(accumulator_trie
+            .store_epoch_and_master_accumulator(&1, &new_epoch_accumulator, &new_master_accumulator)
+            .is_none());
+
+        accumulator_trie
+            .trie
+            .accumulator_storage
+            .writes_until_crash
+            .set(None);
+        assert!(accumulator_trie.recover_accumulators());
+        assert_eq!(
+            epoch_accumulator,
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&1)
+                .unwrap()
+        );
+        assert_eq!(
+            master_accumulator,
+            accumulator_trie.get_master_accumulator().unwrap()
+        );
+        assert_eq!(
+            Some(accumulator_trie.get_master_root_hash(&master_accumulator)),
+            accumulator_trie.get_master_accumulator_root()
+        );
+        assert!(accumulator_trie
+            .trie
+            .accumulator_storage
+            .get_master_accumulator(JOURNAL_NAME)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn recover_accumulators_keeps_old_state_when_journal_is_not_written() {
+        let accumulator_trie = init_crashing_accumulator_trie();
+        let (epoch_accumulator, master_accumulator) = get_epoch_and_master_accumulator(3);
+        accumulator_trie.store_epoch_and_master_accumulator(
+            &1,
+            &epoch_accumulator,
+            &master_accumulator,
+        );
+
+        let (new_epoch_accumulator, new_master_accumulator) = get_epoch_and_master_accumulator(4);
+        accumulator_trie
+            .trie
+            .accumulator_storage
+            .writes_until_crash
+            .set(Some(0));
+        assert!(accumulator_trie
+            .store_epoch_and_master_accumulator(&1, &new_epoch_accumulator, &new_master_accumulator)
+            .is_none());
+
+        accumulator_trie
+            .trie
+            .accumulator_storage
+            .writes_until_crash
+            .set(None);
+        assert!(accumulator_trie.recover_accumulators());
+        assert_eq!(
+            epoch_accumulator,
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&1)
+                .unwrap()
+        );
+        assert_eq!(
+            master_accumulator,
+            accumulator_trie.get_master_accumulator().unwrap()
+        );
+    }
+
     #[test]
     fn get_correct_master_accumulator() {
         let test_vector: [u8; 4] = [1, 2, 5, 7];
         let hash = test_vector.tree_hash_root();
 
         let test_master_accumulator = vec![hash];
-        let mut test_master_accumulator_encoded = test_master_accumulator.as_ssz_bytes();
-
-        let accumulator_storage =
-            AccumulatorFileStorage::new(String::from("not_used"), String::from("not_used"));
-        let mock_master_trie = MockTrie::new(accumulator_storage);
+        let test_master_accumulator_encoded = test_master_accumulator.as_ssz_bytes();
 
-        mock_master_trie
-            .encoded_master_accumulator
-            .borrow_mut()
-            .append(&mut test_master_accumulator_encoded);
-
-        let accumulator_trie = AccumulatorTrie::new(mock_master_trie);
+        let accumulator_trie = init_accumulator_trie();
+        accumulator_trie
+            .trie
+            .update_master_accumulator_encoded(&test_master_accumulator_encoded);
 
         let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
         assert_eq!(test_master_accumulator, master_accumulator);
@@ -196,11 +826,7 @@ mod tests {
 
         let test_master_accumulator = vec![hash];
 
-        let accumulator_storage =
-            AccumulatorFileStorage::new(String::from("not_used"), String::from("not_used"));
-        let mock_master_trie = MockTrie::new(accumulator_storage);
-
-        let accumulator_trie = AccumulatorTrie::new(mock_master_trie);
+        let accumulator_trie = init_accumulator_trie();
         accumulator_trie.store_master_accumulator(&test_master_accumulator);
         //let test_master_accumulator_encoded = test_master_accumulator.as_ssz_bytes();
         let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
@@ -208,6 +834,136 @@ mod tests {
         assert_eq!(test_master_accumulator, master_accumulator);
     }
 
+    #[test]
+    fn store_master_accumulator_persists_root() {
+        let test_master_accumulator: Vec<H256> =
+            (0..5).map(|x| H256::from_low_u64_be(x + 1)).collect();
+
+        let accumulator_trie = init_accumulator_trie();
+        assert_eq!(None, accumulator_trie.get_master_accumulator_root());
+
+        accumulator_trie.store_master_accumulator(&test_master_accumulator);
+
+        let master_var_list: VariableList<_, U16777216> =
+            VariableList::from(test_master_accumulator.clone());
+        let master_root = master_var_list.tree_hash_root();
+        assert_eq!(
+            master_root,
+            accumulator_trie.get_master_root_hash(&test_master_accumulator)
+        );
+        assert_eq!(
+            Some(master_root),
+            accumulator_trie.get_master_accumulator_root()
+        );
+    }
+
+    #[test]
+    fn get_master_root_hash_of_unrelated_master_accumulators() {
+        let accumulator_trie = init_accumulator_trie();
+        let master_accumulator: Vec<H256> = (0..5).map(|x| H256::from_low_u64_be(x + 1)).collect();
+        let mut other_master_accumulator = master_accumulator.clone();
+        other_master_accumulator[0] = H256::from_low_u64_be(100);
+
+        //the cached tree of the first call must not leak into the second
+        for master in [master_accumulator, other_master_accumulator].iter() {
+            let master_var_list: VariableList<_, U16777216> = VariableList::from(master.clone());
+            assert_eq!(
+                master_var_list.tree_hash_root(),
+                accumulator_trie.get_master_root_hash(master)
+            );
+        }
+    }
+
+    #[test]
+    fn store_and_get_historical_master_roots() {
+        let test_master_accumulator: Vec<H256> =
+            (0..4).map(|x| H256::from_low_u64_be(x + 1)).collect();
+
+        let accumulator_trie = init_accumulator_trie();
+
+        assert!(accumulator_trie.store_historical_master_roots(&test_master_accumulator[..2]));
+        //already recorded epochs are not appended again
+        assert!(accumulator_trie.store_historical_master_roots(&test_master_accumulator[..3]));
+        assert_eq!(
+            3 * 32,
+            accumulator_trie
+                .trie
+                .get_master_accumulator_root_history_encoded()
+                .unwrap()
+                .len()
+        );
+
+        let epoch_2_root = accumulator_trie.get_master_root_hash(&test_master_accumulator[..2]);
+        assert_eq!(
+            Some(epoch_2_root),
+            accumulator_trie.get_master_root_by_epoch_number(&2)
+        );
+
+        let block_number = accumulator_trie.epoch_size * 2 - 1;
+        assert_eq!(
+            Some(epoch_2_root),
+            accumulator_trie.get_master_root_by_block_number(&block_number)
+        );
+
+        assert_eq!(None, accumulator_trie.get_master_root_by_epoch_number(&0));
+        assert_eq!(None, accumulator_trie.get_master_root_by_epoch_number(&4));
+    }
+
+    #[test]
+    fn store_historical_master_roots_repairs_partial_trailing_entry() {
+        let test_master_accumulator: Vec<H256> =
+            (0..3).map(|x| H256::from_low_u64_be(x + 1)).collect();
+
+        let accumulator_trie = init_accumulator_trie();
+        assert!(accumulator_trie.store_historical_master_roots(&test_master_accumulator[..2]));
+
+        //a crash in the middle of the next append
+        accumulator_trie
+            .trie
+            .append_master_accumulator_root_history_encoded(&[7; 10]);
+
+        assert!(accumulator_trie.store_historical_master_roots(&test_master_accumulator));
+        assert_eq!(
+            3 * 32,
+            accumulator_trie
+                .trie
+                .get_master_accumulator_root_history_encoded()
+                .unwrap()
+                .len()
+        );
+        for epoch_number in 1..=3 {
+            let master_root =
+                accumulator_trie.get_master_root_hash(&test_master_accumulator[..epoch_number]);
+            assert_eq!(
+                Some(master_root),
+                accumulator_trie.get_master_root_by_epoch_number(&epoch_number)
+            );
+        }
+    }
+
+    #[test]
+    fn store_historical_master_roots_rewrites_history_of_other_master() {
+        let test_master_accumulator: Vec<H256> =
+            (0..3).map(|x| H256::from_low_u64_be(x + 1)).collect();
+        let other_master_accumulator: Vec<H256> =
+            (0..2).map(|x| H256::from_low_u64_be(x + 9)).collect();
+
+        let accumulator_trie = init_accumulator_trie();
+        assert!(accumulator_trie.store_historical_master_roots(&other_master_accumulator));
+        assert!(accumulator_trie.store_historical_master_roots(&test_master_accumulator));
+
+        let master_root = accumulator_trie.get_master_root_hash(&test_master_accumulator);
+        assert_eq!(
+            Some(master_root),
+            accumulator_trie.get_master_root_by_epoch_number(&3)
+        );
+        let master_root = accumulator_trie.get_master_root_hash(&test_master_accumulator[..1]);
+        assert_eq!(
+            Some(master_root),
+            accumulator_trie.get_master_root_by_epoch_number(&1)
+        );
+    }
+
     #[test]
     fn get_correct_epoch_accumulator() {
         let epoch_sedes = EpochSede::new(U256::from(1234), H256::zero());
@@ -216,18 +972,14 @@ mod tests {
         let test_var_list: VariableList<_, U2048> = VariableList::from(test_vector.clone());
         let test_epoch_sede_var_list_encoded = &test_var_list.as_ssz_bytes();
 
-        let accumulator_storage =
-            AccumulatorFileStorage::new(String::from("not_used"), String::from("not_used"));
-        let mock_trie = MockTrie::new(accumulator_storage);
+        let accumulator_trie = init_accumulator_trie();
 
         let epoch_number: usize = 12334;
 
-        mock_trie.encoded_epoch_accumulator.borrow_mut().insert(
-            epoch_number.to_string(),
-            test_epoch_sede_var_list_encoded.to_vec(),
-        );
+        accumulator_trie
+            .trie
+            .update_epoch_accumulator_encoded(&epoch_number, test_epoch_sede_var_list_encoded);
 
-        let accumulator_trie = AccumulatorTrie::new(mock_trie);
         let epoch_accumulator = accumulator_trie
             .get_epoch_accumulator_by_epoch_number(&epoch_number)
             .unwrap();
@@ -240,10 +992,7 @@ mod tests {
         let epoch_sedes = EpochSede::new(U256::from(123455), H256::zero());
         let test_vector: Vec<EpochSede> = vec![epoch_sedes];
 
-        let accumulator_storage =
-            AccumulatorFileStorage::new(String::from("not_used"), String::from("not_used"));
-        let mock_master_trie = MockTrie::new(accumulator_storage);
-        let accumulator_trie = AccumulatorTrie::new(mock_master_trie);
+        let accumulator_trie = init_accumulator_trie();
 
         let epoch_number: usize = 13043;
         accumulator_trie.store_epoch_accumulator_by_epoch_number(&epoch_number, &test_vector);
@@ -254,8 +1003,139 @@ mod tests {
         assert_eq!(test_vector, epoch_accumulator);
     }
 
+    #[test]
+    fn generate_block_proof_for_stored_block() {
+        let test_vector: Vec<EpochSede> = (0..5)
+            .map(|x| EpochSede::new(U256::from(x * 100), H256::from_low_u64_be(x)))
+            .collect();
+
+        let accumulator_trie = init_accumulator_trie();
+
+        let epoch_number: usize = 2;
+        let epoch_hash = accumulator_trie.get_tree_root_hash(&test_vector);
+        accumulator_trie.store_master_accumulator(&vec![H256::zero(), epoch_hash]);
+        accumulator_trie.store_epoch_accumulator_by_epoch_number(&epoch_number, &test_vector);
+
+        let block_number = accumulator_trie.epoch_size + 3;
+        let branch = accumulator_trie.generate_block_proof(&block_number).unwrap();
+        assert_eq!(EPOCH_TREE_DEPTH + 1, branch.len());
+
+        assert!(proof::verify_block_in_epoch(
+            test_vector[3],
+            3,
+            &branch,
+            epoch_hash
+        ));
+
+        assert_eq!(
+            Some(test_vector[3]),
+            accumulator_trie.get_epoch_sede_by_block_number(&block_number)
+        );
+
+        //block is not yet in the epoch accumulator
+        let block_number = accumulator_trie.epoch_size + 5;
+        assert_eq!(None, accumulator_trie.generate_block_proof(&block_number));
+        assert_eq!(
+            None,
+            accumulator_trie.get_epoch_sede_by_block_number(&block_number)
+        );
+
+        //epoch is not in the master accumulator
+        let block_number = accumulator_trie.epoch_size * 2;
+        assert_eq!(None, accumulator_trie.generate_block_proof(&block_number));
+    }
+
+    #[test]
+    fn generate_epoch_proof_for_stored_epoch() {
+        let test_master_accumulator: Vec<H256> =
+            (0..3).map(|x| H256::from_low_u64_be(x + 1)).collect();
+
+        let accumulator_trie = init_accumulator_trie();
+        accumulator_trie.store_master_accumulator(&test_master_accumulator);
+
+        let epoch_number: usize = 3;
+        let branch = accumulator_trie.generate_epoch_proof(&epoch_number).unwrap();
+        assert_eq!(MASTER_TREE_DEPTH + 1, branch.len());
+
+        let master_var_list: VariableList<_, U16777216> =
+            VariableList::from(test_master_accumulator.clone());
+        assert!(proof::verify_epoch_in_master(
+            test_master_accumulator[epoch_number - 1],
+            epoch_number - 1,
+            &branch,
+            master_var_list.tree_hash_root()
+        ));
+
+        assert_eq!(None, accumulator_trie.generate_epoch_proof(&0));
+        assert_eq!(None, accumulator_trie.generate_epoch_proof(&4));
+    }
+
+    #[test]
+    fn generate_epoch_multiproof_for_stored_blocks() {
+        let test_vector: Vec<EpochSede> = (0..40)
+            .map(|x| EpochSede::new(U256::from(x * 100), H256::from_low_u64_be(x)))
+            .collect();
+
+        let accumulator_trie = init_accumulator_trie();
+
+        let epoch_number: usize = 1;
+        let epoch_hash = accumulator_trie.get_tree_root_hash(&test_vector);
+        accumulator_trie.store_master_accumulator(&vec![epoch_hash]);
+        accumulator_trie.store_epoch_accumulator_by_epoch_number(&epoch_number, &test_vector);
+
+        let multiproof = accumulator_trie
+            .generate_epoch_multiproof(&epoch_number, &[20, 10, 11, 12, 10])
+            .unwrap();
+        assert_eq!(vec![10, 11, 12, 20], multiproof.indices);
+        assert_eq!(test_vector[20], multiproof.leaves[3]);
+        assert!(proof::verify_epoch_multiproof(&multiproof, epoch_hash));
+
+        assert_eq!(
+            None,
+            accumulator_trie.generate_epoch_multiproof(&epoch_number, &[40])
+        );
+        assert_eq!(None, accumulator_trie.generate_epoch_multiproof(&2, &[1]));
+    }
+
     #[test]
     fn test_get_correct_root_hash() {
         //how to test?
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn truncated_snappy_accumulators_are_an_error() {
+        let base_path =
+            std::env::temp_dir().join(format!("accumulator_truncated_{}", std::process::id()));
+        let base_path = base_path.to_str().unwrap();
+        let accumulator_storage = AccumulatorFileStorage::new(
+            format!("{}/master", base_path),
+            format!("{}/epoch", base_path),
+        )
+        .with_snappy_compression(true);
+        let accumulator_trie = AccumulatorTrie::new(Trie::new(accumulator_storage));
+
+        let (epoch_accumulator, _) = get_epoch_and_master_accumulator(50);
+        let master_accumulator: Vec<H256> = (0..50).map(|x| H256::from_low_u64_be(x + 1)).collect();
+        assert!(accumulator_trie
+            .store_epoch_and_master_accumulator(&1, &epoch_accumulator, &master_accumulator)
+            .is_some());
+
+        //both files lose the end of their snappy stream
+        for file_path in [
+            format!("{}/master/master_accumulator.txt", base_path),
+            format!("{}/epoch/epoch_accumulator_1.txt", base_path),
+        ]
+        .iter()
+        {
+            let compressed = std::fs::read(file_path).unwrap();
+            std::fs::write(file_path, &compressed[..compressed.len() - 7]).unwrap();
+        }
+
+        let master_result = accumulator_trie.get_master_accumulator();
+        let epoch_result = accumulator_trie.get_epoch_accumulator_by_epoch_number(&1);
+        std::fs::remove_dir_all(base_path).unwrap();
+
+        assert!(master_result.is_err());
+        assert!(epoch_result.is_err());
+    }
+}
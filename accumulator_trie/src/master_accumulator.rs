@@ -0,0 +1,52 @@
+use crate::epoch_sedes::EpochSede;
+use ethereum_types::H256;
+use ssz_derive::{Decode, Encode};
+use ssz_types::{typenum, VariableList};
+use typenum::{U16777216, U2048};
+
+//the accumulator container used by trin
+//historical_epochs only holds completed epochs, the partial epoch is kept in current_epoch
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct MasterAccumulator {
+    pub historical_epochs: VariableList<H256, U16777216>,
+    pub current_epoch: VariableList<EpochSede, U2048>,
+}
+
+impl MasterAccumulator {
+    pub fn new(historical_epochs: Vec<H256>, current_epoch: Vec<EpochSede>) -> MasterAccumulator {
+        MasterAccumulator {
+            historical_epochs: VariableList::from(historical_epochs),
+            current_epoch: VariableList::from(current_epoch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::U256;
+    use ssz::{Decode, Encode};
+
+    #[test]
+    fn encode_and_decode_master_accumulator() {
+        let master_accumulator = MasterAccumulator::new(
+            vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            vec![EpochSede::new(U256::from(5), H256::from_low_u64_be(3))],
+        );
+
+        let encoded = master_accumulator.as_ssz_bytes();
+        //two offsets, two epoch hashes and one epoch sede
+        assert_eq!(4 + 4 + 2 * 32 + 64, encoded.len());
+        assert_eq!(
+            8,
+            u32::from_le_bytes([encoded[0], encoded[1], encoded[2], encoded[3]])
+        );
+        assert_eq!(
+            72,
+            u32::from_le_bytes([encoded[4], encoded[5], encoded[6], encoded[7]])
+        );
+
+        let decoded = MasterAccumulator::from_ssz_bytes(&encoded).unwrap();
+        assert_eq!(master_accumulator, decoded);
+    }
+}
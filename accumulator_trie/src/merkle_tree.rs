@@ -0,0 +1,567 @@
+use crate::epoch_sedes::EpochSede;
+use eth2_hashing::{hash32_concat, ZERO_HASHES};
+use ethereum_types::H256;
+use std::collections::{BTreeSet, HashMap};
+use tree_hash::{mix_in_length, TreeHash};
+
+//List[EpochSede, 2048]
+pub const EPOCH_TREE_DEPTH: usize = 11;
+//List[H256, 16777216]
+pub const MASTER_TREE_DEPTH: usize = 24;
+
+//merkle tree of an ssz list, only the non empty part of every layer is kept
+//nodes outside of it are the zero hashes of their height
+pub struct MerkleTree {
+    depth: usize,
+    layers: Vec<Vec<H256>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<H256>, depth: usize) -> MerkleTree {
+        assert!(
+            leaves.len() <= 1 << depth,
+            "Too many leaves for a tree of depth {}",
+            depth
+        );
+
+        let mut layers = vec![leaves];
+        for height in 0..depth {
+            let layer = &layers[height];
+            let parent_layer = layer
+                .chunks(2)
+                .map(|pair| {
+                    let right = match pair.get(1) {
+                        Some(x) => *x,
+                        None => zero_hash(height),
+                    };
+                    hash_pair(&pair[0], &right)
+                })
+                .collect();
+            layers.push(parent_layer);
+        }
+
+        MerkleTree { depth, layers }
+    }
+
+    //appends a leaf and rehashes only the path from it to the root
+    pub fn push(&mut self, leaf: H256) {
+        let index = self.len();
+        assert!(
+            index < 1 << self.depth,
+            "Tree of depth {} is full",
+            self.depth
+        );
+
+        self.layers[0].push(leaf);
+        self.update_path(index);
+    }
+
+    pub fn replace(&mut self, index: usize, leaf: H256) {
+        self.layers[0][index] = leaf;
+        self.update_path(index);
+    }
+
+    //brings the tree in line with leaves that only changed at the end, like the master accumulator
+    //the cached leaves before the last one are compared, which is cheap next to hashing
+    //the tree is rebuilt when any of them changed, otherwise only the tail is rehashed
+    pub fn update_leaves(&mut self, leaves: &[H256]) {
+        let unchanged_length = self.len().saturating_sub(1);
+        if leaves.len() < self.len()
+            || self.layers[0][..unchanged_length] != leaves[..unchanged_length]
+        {
+            *self = MerkleTree::new(leaves.to_vec(), self.depth);
+            return;
+        }
+
+        for (index, leaf) in leaves.iter().enumerate().skip(unchanged_length) {
+            if index >= self.len() {
+                self.push(*leaf);
+            } else if self.layers[0][index] != *leaf {
+                self.replace(index, *leaf);
+            }
+        }
+    }
+
+    fn update_path(&mut self, index: usize) {
+        for height in 0..self.depth {
+            let parent_index = index >> (height + 1);
+            let parent = hash_pair(
+                &self.node(height, parent_index * 2),
+                &self.node(height, parent_index * 2 + 1),
+            );
+
+            let parent_layer = &mut self.layers[height + 1];
+            if parent_index < parent_layer.len() {
+                parent_layer[parent_index] = parent;
+            } else {
+                parent_layer.push(parent);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers[0].is_empty()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn node(&self, height: usize, index: usize) -> H256 {
+        match self.layers[height].get(index) {
+            Some(x) => *x,
+            None => zero_hash(height),
+        }
+    }
+
+    //root of the data tree, without the length mixed in
+    pub fn root(&self) -> H256 {
+        self.node(self.depth, 0)
+    }
+
+    //the ssz hash_tree_root of the list
+    pub fn hash_tree_root(&self) -> H256 {
+        mix_in_length(&self.root(), self.len())
+    }
+
+    //sibling hashes from the leaf up to the data root, followed by the length chunk
+    pub fn branch(&self, index: usize) -> Vec<H256> {
+        let mut branch: Vec<H256> = (0..self.depth)
+            .map(|height| self.node(height, (index >> height) ^ 1))
+            .collect();
+        branch.push(length_chunk(self.len()));
+
+        branch
+    }
+
+    //helper nodes needed to prove all the leaves at once, ordered by descending generalized index
+    pub fn multiproof(&self, indices: &[usize]) -> Vec<H256> {
+        get_helper_indices(indices, self.depth)
+            .iter()
+            .map(|x| self.node_by_generalized_index(*x))
+            .collect()
+    }
+
+    //generalized indices are those of the whole list, 2 is the data root and 3 the length
+    fn node_by_generalized_index(&self, generalized_index: usize) -> H256 {
+        if generalized_index == 3 {
+            return length_chunk(self.len());
+        }
+
+        let level = log2(generalized_index);
+        let height = self.depth + 1 - level;
+        self.node(height, generalized_index - (1 << level))
+    }
+}
+
+pub fn get_epoch_tree(epoch_accumulator: &[EpochSede]) -> MerkleTree {
+    let leaves = epoch_accumulator
+        .iter()
+        .map(|x| x.tree_hash_root())
+        .collect();
+
+    MerkleTree::new(leaves, EPOCH_TREE_DEPTH)
+}
+
+//checks a branch produced by MerkleTree::branch against the hash_tree_root of the list
+pub fn verify_merkle_branch(
+    leaf: &H256,
+    index: usize,
+    branch: &[H256],
+    depth: usize,
+    root: &H256,
+) -> bool {
+    if branch.len() != depth + 1 || index >= 1 << depth {
+        return false;
+    }
+
+    //the leaf has to be inside the list, and the list inside its limit
+    match decode_length_chunk(&branch[depth]) {
+        Some(length) if (index as u64) < length && length <= 1 << depth => {}
+        _ => return false,
+    }
+
+    let mut node = *leaf;
+    for (height, sibling) in branch[..depth].iter().enumerate() {
+        if (index >> height) & 1 == 1 {
+            node = hash_pair(sibling, &node);
+        } else {
+            node = hash_pair(&node, sibling);
+        }
+    }
+
+    hash_pair(&node, &branch[depth]) == *root
+}
+
+//checks helper nodes produced by MerkleTree::multiproof against the hash_tree_root of the list
+pub fn verify_merkle_multiproof(
+    leaves: &[H256],
+    indices: &[usize],
+    proof: &[H256],
+    depth: usize,
+    root: &H256,
+) -> bool {
+    let unique_indices: BTreeSet<usize> = indices.iter().cloned().collect();
+    if indices.is_empty()
+        || leaves.len() != indices.len()
+        || unique_indices.len() != indices.len()
+        || indices.iter().any(|x| *x >= 1 << depth)
+    {
+        return false;
+    }
+
+    let helper_indices = get_helper_indices(indices, depth);
+    if helper_indices.len() != proof.len() {
+        return false;
+    }
+
+    let mut objects: HashMap<usize, H256> = HashMap::new();
+    for (index, leaf) in indices.iter().zip(leaves.iter()) {
+        objects.insert(leaf_generalized_index(*index, depth), *leaf);
+    }
+    for (generalized_index, node) in helper_indices.iter().zip(proof.iter()) {
+        objects.insert(*generalized_index, *node);
+    }
+
+    //the length chunk is always a helper
+    match decode_length_chunk(&objects[&3]) {
+        Some(length) if indices.iter().all(|x| (*x as u64) < length) && length <= 1 << depth => {}
+        _ => return false,
+    }
+
+    let mut keys: Vec<usize> = objects.keys().cloned().collect();
+    keys.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut position = 0;
+    while position < keys.len() {
+        let key = keys[position];
+        if objects.contains_key(&(key ^ 1)) && !objects.contains_key(&(key / 2)) {
+            let parent = hash_pair(&objects[&((key | 1) ^ 1)], &objects[&(key | 1)]);
+            objects.insert(key / 2, parent);
+            keys.push(key / 2);
+        }
+        position += 1;
+    }
+
+    objects.get(&1) == Some(root)
+}
+
+fn get_helper_indices(indices: &[usize], depth: usize) -> Vec<usize> {
+    let mut branch_indices = BTreeSet::new();
+    let mut path_indices = BTreeSet::new();
+
+    for index in indices.iter() {
+        let mut generalized_index = leaf_generalized_index(*index, depth);
+        while generalized_index > 1 {
+            branch_indices.insert(generalized_index ^ 1);
+            path_indices.insert(generalized_index);
+            generalized_index /= 2;
+        }
+    }
+
+    let mut helper_indices: Vec<usize> =
+        branch_indices.difference(&path_indices).cloned().collect();
+    helper_indices.reverse();
+
+    helper_indices
+}
+
+//the data root sits at generalized index 2 as the length is mixed in above it
+fn leaf_generalized_index(index: usize, depth: usize) -> usize {
+    (2 << depth) + index
+}
+
+fn log2(x: usize) -> usize {
+    (usize::BITS - 1 - x.leading_zeros()) as usize
+}
+
+fn decode_length_chunk(chunk: &H256) -> Option<u64> {
+    let chunk_bytes = chunk.as_bytes();
+    if chunk_bytes[8..].iter().any(|x| *x != 0) {
+        return None;
+    }
+
+    let mut length = [0; 8];
+    length.copy_from_slice(&chunk_bytes[0..8]);
+
+    Some(u64::from_le_bytes(length))
+}
+
+pub fn hash_pair(left: &H256, right: &H256) -> H256 {
+    H256::from(hash32_concat(left.as_bytes(), right.as_bytes()))
+}
+
+pub fn zero_hash(height: usize) -> H256 {
+    H256::from_slice(&ZERO_HASHES[height])
+}
+
+//the length of an ssz list as it is mixed into the root
+pub fn length_chunk(length: usize) -> H256 {
+    let mut chunk = [0; 32];
+    chunk[0..8].copy_from_slice(&(length as u64).to_le_bytes());
+
+    H256::from(chunk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssz_types::{typenum, VariableList};
+    use typenum::{U16777216, U2048};
+
+    fn get_leaves(count: u64) -> Vec<H256> {
+        (0..count).map(|x| H256::from_low_u64_be(x + 1)).collect()
+    }
+
+    #[test]
+    fn root_matches_epoch_variable_list() {
+        for count in [0, 1, 2, 3, 100, 2048].iter() {
+            let leaves = get_leaves(*count);
+            let var_list: VariableList<_, U2048> = VariableList::from(leaves.clone());
+
+            let tree = MerkleTree::new(leaves, EPOCH_TREE_DEPTH);
+            assert_eq!(var_list.tree_hash_root(), tree.hash_tree_root());
+        }
+    }
+
+    #[test]
+    fn root_matches_master_variable_list() {
+        let leaves = get_leaves(37);
+        let var_list: VariableList<_, U16777216> = VariableList::from(leaves.clone());
+
+        let tree = MerkleTree::new(leaves, MASTER_TREE_DEPTH);
+        assert_eq!(var_list.tree_hash_root(), tree.hash_tree_root());
+    }
+
+    #[test]
+    fn pushed_tree_matches_built_tree() {
+        let leaves = get_leaves(37);
+        let mut tree = MerkleTree::new(vec![], EPOCH_TREE_DEPTH);
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            tree.push(*leaf);
+
+            let built_tree = MerkleTree::new(leaves[..index + 1].to_vec(), EPOCH_TREE_DEPTH);
+            assert_eq!(built_tree.hash_tree_root(), tree.hash_tree_root());
+            assert_eq!(built_tree.branch(index / 2), tree.branch(index / 2));
+        }
+    }
+
+    #[test]
+    fn updated_tree_matches_built_tree() {
+        let mut leaves = get_leaves(20);
+        let mut tree = MerkleTree::new(leaves.clone(), MASTER_TREE_DEPTH);
+
+        //last leaf replaced, as the master accumulator does for every block
+        leaves[19] = H256::from_low_u64_be(1000);
+        tree.update_leaves(&leaves);
+        let built_tree = MerkleTree::new(leaves.clone(), MASTER_TREE_DEPTH);
+        assert_eq!(built_tree.hash_tree_root(), tree.hash_tree_root());
+
+        leaves.push(H256::from_low_u64_be(1001));
+        tree.update_leaves(&leaves);
+        let built_tree = MerkleTree::new(leaves.clone(), MASTER_TREE_DEPTH);
+        assert_eq!(built_tree.hash_tree_root(), tree.hash_tree_root());
+
+        leaves.truncate(5);
+        tree.update_leaves(&leaves);
+        let built_tree = MerkleTree::new(leaves, MASTER_TREE_DEPTH);
+        assert_eq!(built_tree.hash_tree_root(), tree.hash_tree_root());
+    }
+
+    #[test]
+    fn updated_tree_is_rebuilt_when_an_early_leaf_changes() {
+        let mut leaves = get_leaves(20);
+        let mut tree = MerkleTree::new(leaves.clone(), MASTER_TREE_DEPTH);
+
+        leaves[3] = H256::from_low_u64_be(1000);
+        leaves.push(H256::from_low_u64_be(1001));
+        tree.update_leaves(&leaves);
+        let built_tree = MerkleTree::new(leaves, MASTER_TREE_DEPTH);
+        assert_eq!(built_tree.hash_tree_root(), tree.hash_tree_root());
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_fails_when_tree_is_full() {
+        let mut tree = MerkleTree::new(get_leaves(4), 2);
+        tree.push(H256::zero());
+    }
+
+    #[test]
+    fn verify_branch_of_every_leaf() {
+        let leaves = get_leaves(13);
+        let tree = MerkleTree::new(leaves.clone(), EPOCH_TREE_DEPTH);
+        let root = tree.hash_tree_root();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let branch = tree.branch(index);
+            assert!(verify_merkle_branch(
+                leaf,
+                index,
+                &branch,
+                EPOCH_TREE_DEPTH,
+                &root
+            ));
+        }
+    }
+
+    #[test]
+    fn verify_branch_fails_on_bad_input() {
+        let leaves = get_leaves(13);
+        let tree = MerkleTree::new(leaves.clone(), EPOCH_TREE_DEPTH);
+        let root = tree.hash_tree_root();
+        let branch = tree.branch(4);
+
+        //wrong leaf and wrong index
+        assert!(!verify_merkle_branch(
+            &leaves[5],
+            4,
+            &branch,
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+        assert!(!verify_merkle_branch(
+            &leaves[4],
+            5,
+            &branch,
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+
+        //index outside of the list
+        assert!(!verify_merkle_branch(
+            &leaves[4],
+            4100,
+            &branch,
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+
+        //truncated branch
+        let truncated = &branch[1..];
+        assert!(!verify_merkle_branch(
+            &leaves[4],
+            4,
+            truncated,
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+
+        //tampered length mix-in
+        let mut bad_length = branch.clone();
+        bad_length[EPOCH_TREE_DEPTH] = length_chunk(14);
+        assert!(!verify_merkle_branch(
+            &leaves[4],
+            4,
+            &bad_length,
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+    }
+
+    #[test]
+    fn verify_multiproof_of_many_leaves() {
+        let leaves = get_leaves(300);
+        let tree = MerkleTree::new(leaves.clone(), EPOCH_TREE_DEPTH);
+        let root = tree.hash_tree_root();
+
+        let indices: Vec<usize> = vec![299, 3, 4, 5, 6, 100, 101];
+        let proof_leaves: Vec<H256> = indices.iter().map(|x| leaves[*x]).collect();
+        let proof = tree.multiproof(&indices);
+
+        //should be smaller than the sum of the single branches
+        assert!(proof.len() < indices.len() * (EPOCH_TREE_DEPTH + 1));
+        assert!(verify_merkle_multiproof(
+            &proof_leaves,
+            &indices,
+            &proof,
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+    }
+
+    #[test]
+    fn verify_multiproof_of_single_leaf_matches_branch() {
+        let leaves = get_leaves(9);
+        let tree = MerkleTree::new(leaves, EPOCH_TREE_DEPTH);
+
+        assert_eq!(tree.branch(6), tree.multiproof(&[6]));
+    }
+
+    #[test]
+    fn verify_multiproof_fails_on_bad_input() {
+        let leaves = get_leaves(20);
+        let tree = MerkleTree::new(leaves.clone(), EPOCH_TREE_DEPTH);
+        let root = tree.hash_tree_root();
+
+        let indices: Vec<usize> = vec![2, 7, 8];
+        let proof_leaves: Vec<H256> = indices.iter().map(|x| leaves[*x]).collect();
+        let proof = tree.multiproof(&indices);
+
+        //leaves swapped
+        let swapped_leaves = vec![proof_leaves[1], proof_leaves[0], proof_leaves[2]];
+        assert!(!verify_merkle_multiproof(
+            &swapped_leaves,
+            &indices,
+            &proof,
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+
+        //missing helper
+        assert!(!verify_merkle_multiproof(
+            &proof_leaves,
+            &indices,
+            &proof[1..],
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+
+        //tampered helper
+        let mut bad_proof = proof.clone();
+        bad_proof[0] = H256::zero();
+        assert!(!verify_merkle_multiproof(
+            &proof_leaves,
+            &indices,
+            &bad_proof,
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+
+        //duplicated index
+        assert!(!verify_merkle_multiproof(
+            &[leaves[2], leaves[2]],
+            &[2, 2],
+            &tree.multiproof(&[2]),
+            EPOCH_TREE_DEPTH,
+            &root
+        ));
+    }
+
+    #[test]
+    fn branch_folds_back_to_root() {
+        let leaves = get_leaves(5);
+        let tree = MerkleTree::new(leaves.clone(), EPOCH_TREE_DEPTH);
+
+        let index = 3;
+        let branch = tree.branch(index);
+        assert_eq!(EPOCH_TREE_DEPTH + 1, branch.len());
+
+        let mut node = leaves[index];
+        for (height, sibling) in branch[..EPOCH_TREE_DEPTH].iter().enumerate() {
+            if (index >> height) & 1 == 1 {
+                node = hash_pair(sibling, &node);
+            } else {
+                node = hash_pair(&node, sibling);
+            }
+        }
+        node = hash_pair(&node, &branch[EPOCH_TREE_DEPTH]);
+
+        assert_eq!(tree.hash_tree_root(), node);
+    }
+}
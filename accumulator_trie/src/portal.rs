@@ -0,0 +1,185 @@
+use crate::epoch_sedes::EpochSede;
+use eth2_hashing::hash;
+use ethereum_types::H256;
+use ssz::{Decode, DecodeError, Encode};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{typenum, FixedVector, VariableList};
+use tree_hash::TreeHash;
+use typenum::{U13, U2048};
+
+//content key selector of a block header in the portal history network
+pub const BLOCK_HEADER_SELECTOR: u8 = 0x00;
+//content key selector of an epoch accumulator in the portal history network
+pub const EPOCH_ACCUMULATOR_SELECTOR: u8 = 0x03;
+
+//proof of the block hash in the epoch accumulator:
+//total_difficulty sibling, 11 epoch siblings and the length mix-in
+pub type AccumulatorProof = FixedVector<H256, U13>;
+
+//ssz Union[None, AccumulatorProof] with a one byte selector
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockHeaderProof {
+    None,
+    AccumulatorProof(AccumulatorProof),
+}
+
+impl Encode for BlockHeaderProof {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        match self {
+            BlockHeaderProof::None => buf.push(0),
+            BlockHeaderProof::AccumulatorProof(proof) => {
+                buf.push(1);
+                proof.ssz_append(buf);
+            }
+        }
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        match self {
+            BlockHeaderProof::None => 1,
+            BlockHeaderProof::AccumulatorProof(proof) => 1 + proof.ssz_bytes_len(),
+        }
+    }
+}
+
+impl Decode for BlockHeaderProof {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match bytes.split_first() {
+            Some((0, [])) => Ok(BlockHeaderProof::None),
+            Some((1, proof_bytes)) => Ok(BlockHeaderProof::AccumulatorProof(
+                AccumulatorProof::from_ssz_bytes(proof_bytes)?,
+            )),
+            Some((selector, _)) => Err(DecodeError::BytesInvalid(format!(
+                "Invalid union selector: {}",
+                selector
+            ))),
+            None => Err(DecodeError::InvalidByteLength {
+                len: 0,
+                expected: 1,
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct BlockHeaderWithProof {
+    pub header: VariableList<u8, U2048>,
+    pub proof: BlockHeaderProof,
+}
+
+pub fn block_header_content_key(block_hash: &H256) -> Vec<u8> {
+    let mut content_key = vec![BLOCK_HEADER_SELECTOR];
+    content_key.extend_from_slice(block_hash.as_bytes());
+
+    content_key
+}
+
+pub fn epoch_accumulator_content_key(epoch_root: &H256) -> Vec<u8> {
+    let mut content_key = vec![EPOCH_ACCUMULATOR_SELECTOR];
+    content_key.extend_from_slice(epoch_root.as_bytes());
+
+    content_key
+}
+
+//the content id is the sha256 of the content key
+pub fn content_id(content_key: &[u8]) -> H256 {
+    H256::from_slice(&hash(content_key))
+}
+
+//extends the branch of the epoch_sede down to its block_hash field
+pub fn get_accumulator_proof(epoch_sede: &EpochSede, epoch_branch: &[H256]) -> AccumulatorProof {
+    let mut proof = vec![epoch_sede.total_difficulty().tree_hash_root()];
+    proof.extend_from_slice(epoch_branch);
+
+    AccumulatorProof::from(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle_tree::{MerkleTree, EPOCH_TREE_DEPTH};
+    use crate::proof::verify_block_hash_in_epoch;
+    use ethereum_types::U256;
+    use std::str::FromStr;
+
+    #[test]
+    fn accumulator_proof_verifies_block_hash() {
+        let epoch_accumulator: Vec<EpochSede> = (0..6)
+            .map(|x| EpochSede::new(U256::from(x * 17), H256::from_low_u64_be(x + 1)))
+            .collect();
+        let leaves = epoch_accumulator
+            .iter()
+            .map(|x| x.tree_hash_root())
+            .collect();
+        let epoch_tree = MerkleTree::new(leaves, EPOCH_TREE_DEPTH);
+
+        let proof = get_accumulator_proof(&epoch_accumulator[4], &epoch_tree.branch(4));
+        assert!(verify_block_hash_in_epoch(
+            epoch_accumulator[4].block_hash(),
+            4,
+            &proof,
+            epoch_tree.hash_tree_root()
+        ));
+    }
+
+    #[test]
+    fn encode_and_decode_block_header_with_proof() {
+        let proof = AccumulatorProof::from(vec![H256::from_low_u64_be(9); 13]);
+        let header_with_proof = BlockHeaderWithProof {
+            header: VariableList::from(vec![0xf9, 0x02, 0x14]),
+            proof: BlockHeaderProof::AccumulatorProof(proof),
+        };
+
+        let encoded = header_with_proof.as_ssz_bytes();
+        //offset of header, offset of proof, header, selector and proof
+        assert_eq!(4 + 4 + 3 + 1 + 13 * 32, encoded.len());
+        assert_eq!(1, encoded[11]);
+
+        let decoded = BlockHeaderWithProof::from_ssz_bytes(&encoded).unwrap();
+        assert_eq!(header_with_proof, decoded);
+    }
+
+    #[test]
+    fn decode_block_header_proof_fails_on_unknown_selector() {
+        assert!(BlockHeaderProof::from_ssz_bytes(&[2]).is_err());
+        assert!(BlockHeaderProof::from_ssz_bytes(&[]).is_err());
+        assert_eq!(
+            BlockHeaderProof::None,
+            BlockHeaderProof::from_ssz_bytes(&[0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn content_key_is_selector_and_block_hash() {
+        let block_hash = H256::from_low_u64_be(0xabcd);
+        let content_key = block_header_content_key(&block_hash);
+
+        assert_eq!(33, content_key.len());
+        assert_eq!(BLOCK_HEADER_SELECTOR, content_key[0]);
+        assert_eq!(block_hash.as_bytes(), &content_key[1..]);
+    }
+
+    #[test]
+    fn epoch_accumulator_content_key_and_id_match_portal_spec() {
+        let epoch_root =
+            H256::from_str("e242814b90ed3950e13aac7e56ce116540c71b41d1516605aada26c6c07cc491")
+                .unwrap();
+
+        let content_key = epoch_accumulator_content_key(&epoch_root);
+        assert_eq!(EPOCH_ACCUMULATOR_SELECTOR, content_key[0]);
+        assert_eq!(epoch_root.as_bytes(), &content_key[1..]);
+
+        let expected_content_id =
+            H256::from_str("9fb2175e76c6989e0fdac3ee10c40d2a81eb176af32e1c16193e3904fe56896e")
+                .unwrap();
+        assert_eq!(expected_content_id, content_id(&content_key));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::epoch_sedes::EpochSede;
+use crate::merkle_tree::{
+    hash_pair, verify_merkle_branch, verify_merkle_multiproof, EPOCH_TREE_DEPTH, MASTER_TREE_DEPTH,
+};
+use ethereum_types::H256;
+use tree_hash::TreeHash;
+
+//covers many blocks of one epoch, indices are positions in the epoch accumulator
+//helpers are ordered by descending generalized index as in the ssz multiproof spec
+#[derive(Clone, Debug, PartialEq)]
+pub struct EpochMultiproof {
+    pub indices: Vec<usize>,
+    pub leaves: Vec<EpochSede>,
+    pub helpers: Vec<H256>,
+}
+
+//these only need the proof and a trusted root, no accumulator files are read
+
+//index is the position of the block in its epoch accumulator
+pub fn verify_block_in_epoch(
+    leaf: EpochSede,
+    index: usize,
+    branch: &[H256],
+    epoch_root: H256,
+) -> bool {
+    verify_merkle_branch(
+        &leaf.tree_hash_root(),
+        index,
+        branch,
+        EPOCH_TREE_DEPTH,
+        &epoch_root,
+    )
+}
+
+//proof is the total_difficulty sibling followed by the branch of the epoch_sede
+pub fn verify_block_hash_in_epoch(
+    block_hash: H256,
+    index: usize,
+    proof: &[H256],
+    epoch_root: H256,
+) -> bool {
+    match proof.split_first() {
+        Some((total_difficulty, branch)) => {
+            let leaf = hash_pair(&block_hash, total_difficulty);
+            verify_merkle_branch(&leaf, index, branch, EPOCH_TREE_DEPTH, &epoch_root)
+        }
+        None => false,
+    }
+}
+
+//index is the position in the master accumulator, epoch N is at index N - 1
+pub fn verify_epoch_in_master(
+    epoch_root: H256,
+    index: usize,
+    branch: &[H256],
+    master_root: H256,
+) -> bool {
+    verify_merkle_branch(&epoch_root, index, branch, MASTER_TREE_DEPTH, &master_root)
+}
+
+pub fn verify_epoch_multiproof(multiproof: &EpochMultiproof, epoch_root: H256) -> bool {
+    let leaves: Vec<H256> = multiproof
+        .leaves
+        .iter()
+        .map(|x| x.tree_hash_root())
+        .collect();
+
+    verify_merkle_multiproof(
+        &leaves,
+        &multiproof.indices,
+        &multiproof.helpers,
+        EPOCH_TREE_DEPTH,
+        &epoch_root,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle_tree::MerkleTree;
+    use ethereum_types::U256;
+
+    fn get_epoch_accumulator() -> Vec<EpochSede> {
+        (0..10)
+            .map(|x| EpochSede::new(U256::from(x * 1000), H256::from_low_u64_be(x + 1)))
+            .collect()
+    }
+
+    #[test]
+    fn verify_block_in_epoch_successfully() {
+        let epoch_accumulator = get_epoch_accumulator();
+        let leaves = epoch_accumulator
+            .iter()
+            .map(|x| x.tree_hash_root())
+            .collect();
+        let epoch_tree = MerkleTree::new(leaves, EPOCH_TREE_DEPTH);
+        let epoch_root = epoch_tree.hash_tree_root();
+
+        let branch = epoch_tree.branch(7);
+        assert!(verify_block_in_epoch(
+            epoch_accumulator[7],
+            7,
+            &branch,
+            epoch_root
+        ));
+        assert!(!verify_block_in_epoch(
+            epoch_accumulator[6],
+            7,
+            &branch,
+            epoch_root
+        ));
+        assert!(!verify_block_in_epoch(
+            epoch_accumulator[7],
+            7,
+            &branch,
+            H256::zero()
+        ));
+    }
+
+    #[test]
+    fn verify_epoch_multiproof_successfully() {
+        let epoch_accumulator = get_epoch_accumulator();
+        let leaves = epoch_accumulator
+            .iter()
+            .map(|x| x.tree_hash_root())
+            .collect();
+        let epoch_tree = MerkleTree::new(leaves, EPOCH_TREE_DEPTH);
+        let epoch_root = epoch_tree.hash_tree_root();
+
+        let indices = vec![1, 2, 3, 9];
+        let mut multiproof = EpochMultiproof {
+            leaves: indices.iter().map(|x| epoch_accumulator[*x]).collect(),
+            helpers: epoch_tree.multiproof(&indices),
+            indices,
+        };
+        assert!(verify_epoch_multiproof(&multiproof, epoch_root));
+
+        multiproof.leaves[3] = EpochSede::new(U256::from(1), H256::zero());
+        assert!(!verify_epoch_multiproof(&multiproof, epoch_root));
+    }
+
+    #[test]
+    fn verify_epoch_in_master_successfully() {
+        let master_accumulator: Vec<H256> = (0..4).map(|x| H256::from_low_u64_be(x + 1)).collect();
+        let master_tree = MerkleTree::new(master_accumulator.clone(), MASTER_TREE_DEPTH);
+        let master_root = master_tree.hash_tree_root();
+
+        let branch = master_tree.branch(2);
+        assert!(verify_epoch_in_master(
+            master_accumulator[2],
+            2,
+            &branch,
+            master_root
+        ));
+        assert!(!verify_epoch_in_master(
+            master_accumulator[2],
+            3,
+            &branch,
+            master_root
+        ));
+    }
+}
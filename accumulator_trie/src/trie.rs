@@ -1,31 +1,73 @@
-use accumulator_storage::accumulator_storage::AccumulatorFileStorage;
+use accumulator_storage::accumulator_store::{
+    get_epoch_accumulator_name, AccumulatorStore, JournalEntry, MASTER_ACCUMULATOR_NAME,
+    MASTER_ROOT_HISTORY_NAME, MASTER_ROOT_NAME,
+};
 pub trait TrieTrait  {
-    fn new(accumulator_storage: AccumulatorFileStorage) -> Self;
-    fn get_master_accumulator_encoded(&self) -> Vec<u8>;
-    fn update_master_accumulator_encoded(&self, trie_encoded: &Vec<u8>) -> bool;
+    type Store: AccumulatorStore;
+
+    fn new(accumulator_storage: Self::Store) -> Self;
+    fn get_master_accumulator_encoded(&self) -> Result<Vec<u8>, String>;
+    fn update_master_accumulator_encoded(&self, trie_encoded: &[u8]) -> bool;
+    fn get_master_accumulator_root_encoded(&self) -> Result<Vec<u8>, String>;
+    fn update_master_accumulator_root_encoded(&self, root_encoded: &[u8]) -> bool;
+    fn get_master_accumulator_root_history_encoded(&self) -> Result<Vec<u8>, String>;
+    fn append_master_accumulator_root_history_encoded(&self, root_encoded: &[u8]) -> bool;
+    fn update_master_accumulator_root_history_encoded(&self, root_history_encoded: &[u8]) -> bool;
     fn get_file_name_from_epoch_number(&self, epoch_number: &usize) -> String;
-    fn get_epoch_accumulator_encoded(&self, epoch_number: &usize) -> Vec<u8>;
-    fn update_epoch_accumulator_encoded(
+    fn get_epoch_accumulator_encoded(&self, epoch_number: &usize) -> Result<Vec<u8>, String>;
+    fn update_epoch_accumulator_encoded(&self, epoch_number: &usize, trie_encoded: &[u8]) -> bool;
+    fn commit_accumulators_encoded(
         &self,
         epoch_number: &usize,
-        trie_encoded: &Vec<u8>,
+        epoch_encoded: &[u8],
+        master_encoded: &[u8],
+        root_encoded: &[u8],
     ) -> bool;
+    fn commit_master_accumulator_encoded(&self, master_encoded: &[u8], root_encoded: &[u8])
+        -> bool;
+    fn recover_accumulators(&self) -> bool;
 }
 
-pub struct Trie {
-    pub accumulator_storage: AccumulatorFileStorage,
+pub struct Trie<S: AccumulatorStore> {
+    pub accumulator_storage: S,
     pub file_name: String,
+    pub root_file_name: String,
+    pub root_history_file_name: String,
 }
 
-impl TrieTrait for Trie {
-    fn new(accumulator_storage: AccumulatorFileStorage) -> Trie {
+impl<S: AccumulatorStore> Trie<S> {
+    //the master accumulator is the largest blob, so it is the commit point and is never journaled
+    fn get_master_journal_entries(
+        &self,
+        master_encoded: &[u8],
+        root_encoded: &[u8],
+    ) -> Vec<JournalEntry> {
+        vec![
+            JournalEntry::Master {
+                name: self.root_file_name.clone(),
+                content: root_encoded.to_vec(),
+            },
+            JournalEntry::Master {
+                name: self.file_name.clone(),
+                content: master_encoded.to_vec(),
+            },
+        ]
+    }
+}
+
+impl<S: AccumulatorStore> TrieTrait for Trie<S> {
+    type Store = S;
+
+    fn new(accumulator_storage: S) -> Trie<S> {
         Trie {
             accumulator_storage,
-            file_name: String::from("master_accumulator.txt"),
+            file_name: String::from(MASTER_ACCUMULATOR_NAME),
+            root_file_name: String::from(MASTER_ROOT_NAME),
+            root_history_file_name: String::from(MASTER_ROOT_HISTORY_NAME),
         }
     }
 
-    fn get_master_accumulator_encoded(&self) -> Vec<u8> {
+    fn get_master_accumulator_encoded(&self) -> Result<Vec<u8>, String> {
         //this will create the file if the file does not exist
         self.accumulator_storage
             .create_new_master_accumulator_file(&self.file_name);
@@ -38,7 +80,7 @@ impl TrieTrait for Trie {
             master_accumulator_file_content
     }
 
-    fn update_master_accumulator_encoded(&self, trie_encoded: &Vec<u8>) -> bool {
+    fn update_master_accumulator_encoded(&self, trie_encoded: &[u8]) -> bool {
         let result = self
             .accumulator_storage
             .write_master_accumulator(&self.file_name, trie_encoded);
@@ -46,11 +88,48 @@ impl TrieTrait for Trie {
         result
     }
 
+    fn get_master_accumulator_root_encoded(&self) -> Result<Vec<u8>, String> {
+        self.accumulator_storage
+            .get_master_accumulator(&self.root_file_name)
+    }
+
+    fn update_master_accumulator_root_encoded(&self, root_encoded: &[u8]) -> bool {
+        //the root file is created alongside the master accumulator file
+        self.accumulator_storage
+            .create_new_master_accumulator_file(&self.root_file_name);
+
+        self.accumulator_storage
+            .write_master_accumulator(&self.root_file_name, root_encoded)
+    }
+
+    fn get_master_accumulator_root_history_encoded(&self) -> Result<Vec<u8>, String> {
+        self.accumulator_storage
+            .get_master_accumulator(&self.root_history_file_name)
+    }
+
+    //the history is append only, existing entries are never rewritten
+    fn append_master_accumulator_root_history_encoded(&self, root_encoded: &[u8]) -> bool {
+        self.accumulator_storage
+            .create_new_master_accumulator_file(&self.root_history_file_name);
+
+        self.accumulator_storage
+            .append_master_accumulator(&self.root_history_file_name, root_encoded)
+    }
+
+    //only used to repair a history that no longer matches the master accumulator
+    fn update_master_accumulator_root_history_encoded(&self, root_history_encoded: &[u8]) -> bool {
+        self.accumulator_storage
+            .create_new_master_accumulator_file(&self.root_history_file_name);
+
+        self.accumulator_storage
+            .write_master_accumulator(&self.root_history_file_name, root_history_encoded)
+    }
+
     fn get_file_name_from_epoch_number(&self, epoch_number: &usize) -> String {
-        format!("epoch_accumulator_{}.txt", epoch_number)
+        get_epoch_accumulator_name(epoch_number)
     }
 
-    fn get_epoch_accumulator_encoded(&self, epoch_number: &usize) -> Vec<u8> {
+    fn get_epoch_accumulator_encoded(&self, epoch_number: &usize) -> Result<Vec<u8>, String> {
         //this will create the file if the file does not exist
         let file_name = self.get_file_name_from_epoch_number(epoch_number);
         self.accumulator_storage
@@ -65,8 +144,11 @@ impl TrieTrait for Trie {
         byte_content
     }
 
-    fn update_epoch_accumulator_encoded(&self, epoch_number: &usize, trie_encoded: &Vec<u8>) -> bool {
+    fn update_epoch_accumulator_encoded(&self, epoch_number: &usize, trie_encoded: &[u8]) -> bool {
         let file_name = self.get_file_name_from_epoch_number(epoch_number);
+        //an imported epoch may not have a file yet
+        self.accumulator_storage
+            .create_new_epoch_accumulator_file(&file_name);
 
         let result = self
             .accumulator_storage
@@ -74,123 +156,147 @@ impl TrieTrait for Trie {
 
         result
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serial_test::serial;
-    use std::fs;
-    use std::path::PathBuf;
-
-    fn init_acculumulator_file_storage() -> AccumulatorFileStorage {
-        let accumulator_storage = AccumulatorFileStorage::new(
-            String::from("test_master_path"),
-            String::from("test_epoch_path"),
-        );
-        accumulator_storage
+    //the epoch and root are journaled and the master is the commit point, so they never disagree after a crash
+    fn commit_accumulators_encoded(
+        &self,
+        epoch_number: &usize,
+        epoch_encoded: &[u8],
+        master_encoded: &[u8],
+        root_encoded: &[u8],
+    ) -> bool {
+        let mut entries = vec![JournalEntry::Epoch {
+            name: self.get_file_name_from_epoch_number(epoch_number),
+            content: epoch_encoded.to_vec(),
+        }];
+        entries.extend(self.get_master_journal_entries(master_encoded, root_encoded));
+
+        self.accumulator_storage.commit(&entries)
     }
 
-    fn init_trie(accumulator_storage: AccumulatorFileStorage) -> Trie {
-        let trie = Trie::new(accumulator_storage);
-
-        trie
+    //for a master accumulator that is stored without an epoch, like an import
+    fn commit_master_accumulator_encoded(
+        &self,
+        master_encoded: &[u8],
+        root_encoded: &[u8],
+    ) -> bool {
+        let entries = self.get_master_journal_entries(master_encoded, root_encoded);
+        self.accumulator_storage.commit(&entries)
     }
 
-    fn concat_file_path(base_path: &str, file_name: &str) -> String {
-        let mut full_path = String::new();
-        full_path.push_str(base_path);
-        full_path.push_str("/");
-        full_path.push_str(file_name);
-
-        full_path
+    fn recover_accumulators(&self) -> bool {
+        self.accumulator_storage.recover()
     }
+}
 
-    fn create_file(base_path: &str, file_path: &str, content: &Vec<u8>) {
-        let mut path = PathBuf::new();
-        path.push(&file_path);
-        std::fs::create_dir_all(&base_path).unwrap();
-        std::fs::write(&path, content).unwrap();
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accumulator_storage::memory_storage::AccumulatorMemoryStorage;
+
+    fn init_trie() -> Trie<AccumulatorMemoryStorage> {
+        let trie = Trie::new(AccumulatorMemoryStorage::new());
 
-    fn delete_file(base_path: &str, file_path: &str) {
-        fs::remove_file(&file_path).unwrap();
-        fs::remove_dir(&base_path).unwrap();
+        trie
     }
 
     #[test]
-    #[serial]
     fn get_empty_array_when_master_trie_is_empty() {
-        let accumulator_storage = init_acculumulator_file_storage();
-        let base_path = String::from(&accumulator_storage.master_file_path);
-        let master_trie = init_trie(accumulator_storage);
+        let master_trie = init_trie();
 
-        let trie = master_trie.get_master_accumulator_encoded();
+        let trie = master_trie.get_master_accumulator_encoded().unwrap();
         let empty_vector: Vec<u8> = Vec::new();
 
         assert_eq!(empty_vector, trie);
-        let file_path = concat_file_path(&base_path, &master_trie.file_name);
-
-        delete_file(&base_path, &file_path)
+        assert_eq!(
+            vec![master_trie.file_name.clone()],
+            master_trie.accumulator_storage.list_master_accumulators()
+        );
     }
 
     #[test]
-    #[serial]
     fn get_correct_array_from_master_trie() {
         let test_vec: Vec<u8> = vec![0, 1, 3, 4, 5];
-        let accumulator_storage = init_acculumulator_file_storage();
-        let base_path = String::from(&accumulator_storage.master_file_path);
-        let master_trie = init_trie(accumulator_storage);
+        let master_trie = init_trie();
 
-        let file_path = concat_file_path(&base_path, &master_trie.file_name);
+        let storage = &master_trie.accumulator_storage;
+        storage.create_new_master_accumulator_file(&master_trie.file_name);
+        storage.write_master_accumulator(&master_trie.file_name, &test_vec);
 
-        create_file(&base_path, &file_path, &test_vec);
-
-        let vec_result = master_trie.get_master_accumulator_encoded();
+        let vec_result = master_trie.get_master_accumulator_encoded().unwrap();
 
         assert_eq!(test_vec, vec_result);
-
-        delete_file(&base_path, &file_path);
     }
 
     #[test]
-    #[serial]
     fn write_to_master_trie_successfully() {
         let test_vec: Vec<u8> = vec![0, 3, 5, 34, 5, 1];
-        let empty_vec: Vec<u8> = Vec::new();
-        let accumulator_storage = init_acculumulator_file_storage();
-        let base_path = String::from(&accumulator_storage.master_file_path);
-        let master_trie = init_trie(accumulator_storage);
+        let master_trie = init_trie();
 
-        let file_path = concat_file_path(&base_path, &master_trie.file_name);
-
-        create_file(&base_path, &file_path, &empty_vec);
+        master_trie
+            .accumulator_storage
+            .create_new_master_accumulator_file(&master_trie.file_name);
 
         let update_result = master_trie.update_master_accumulator_encoded(&test_vec);
         assert_eq!(true, update_result);
 
-        let vec_result = master_trie.get_master_accumulator_encoded();
+        let vec_result = master_trie.get_master_accumulator_encoded().unwrap();
         assert_eq!(test_vec, vec_result);
-
-        delete_file(&base_path, &file_path);
     }
 
     #[test]
-    #[serial]
     fn write_to_file_not_successfully_when_file_not_found() {
         let test_vec: Vec<u8> = vec![0, 3, 5, 34, 5, 1];
-        let accumulator_storage = init_acculumulator_file_storage();
-        let master_trie = init_trie(accumulator_storage);
+        let master_trie = init_trie();
 
         let update_result = master_trie.update_master_accumulator_encoded(&test_vec);
         assert_eq!(false, update_result);
     }
-    
+
+    #[test]
+    fn write_and_get_master_accumulator_root() {
+        let test_vec: Vec<u8> = b"0x1234".to_vec();
+        let master_trie = init_trie();
+
+        let empty_vector: Vec<u8> = Vec::new();
+        assert_eq!(
+            empty_vector,
+            master_trie.get_master_accumulator_root_encoded().unwrap()
+        );
+
+        let update_result = master_trie.update_master_accumulator_root_encoded(&test_vec);
+        assert!(update_result);
+        assert_eq!(
+            test_vec,
+            master_trie.get_master_accumulator_root_encoded().unwrap()
+        );
+    }
+
+    #[test]
+    fn append_and_get_master_accumulator_root_history() {
+        let master_trie = init_trie();
+
+        let empty_vector: Vec<u8> = Vec::new();
+        assert_eq!(
+            empty_vector,
+            master_trie
+                .get_master_accumulator_root_history_encoded()
+                .unwrap()
+        );
+
+        assert!(master_trie.append_master_accumulator_root_history_encoded(&[1, 2]));
+        assert!(master_trie.append_master_accumulator_root_history_encoded(&[3]));
+        assert_eq!(
+            vec![1, 2, 3],
+            master_trie
+                .get_master_accumulator_root_history_encoded()
+                .unwrap()
+        );
+    }
 
     #[test]
     fn able_to_generate_correct_epoch_file_name() {
-        let accumulator_storage = init_acculumulator_file_storage();
-        let epoch_trie = init_trie(accumulator_storage);
+        let epoch_trie = init_trie();
 
         let epoch_number: usize = 1234556789;
         let correct_file_name = "epoch_accumulator_1234556789.txt";
@@ -198,24 +304,23 @@ mod tests {
         let file_name = epoch_trie.get_file_name_from_epoch_number(&epoch_number);
         assert_eq!(correct_file_name, file_name);
     }
-    
 
     #[test]
-    #[serial]
     fn get_empty_array_when_epoch_trie_is_empty() {
-        let accumulator_storage = init_acculumulator_file_storage();
-        let base_path = String::from(&accumulator_storage.epoch_file_path);
-        let epoch_trie = init_trie(accumulator_storage);
+        let epoch_trie = init_trie();
 
         let epoch_number = 134563743;
-        let trie = epoch_trie.get_epoch_accumulator_encoded(&epoch_number);
+        let trie = epoch_trie
+            .get_epoch_accumulator_encoded(&epoch_number)
+            .unwrap();
         let empty_vector: Vec<u8> = Vec::new();
 
         assert_eq!(empty_vector, trie);
 
         let file_name = epoch_trie.get_file_name_from_epoch_number(&epoch_number);
-        let file_path = concat_file_path(&base_path, &file_name);
-
-        delete_file(&base_path, &file_path)
+        assert_eq!(
+            vec![file_name],
+            epoch_trie.accumulator_storage.list_epoch_accumulators()
+        );
     }
 }
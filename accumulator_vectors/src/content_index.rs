@@ -0,0 +1,73 @@
+use accumulator_trie::portal::{content_id, epoch_accumulator_content_key};
+use ethereum_types::{H256, U256};
+use serde_derive::Serialize;
+
+//one entry per completed epoch, used to seed a portal network with the epoch accumulators
+#[derive(Debug, Serialize)]
+pub struct EpochIndexEntry {
+    pub epoch_number: usize,
+    pub epoch_root: H256,
+    #[serde(serialize_with = "impl_serde::serialize::serialize")]
+    pub content_key: Vec<u8>,
+    pub content_id: H256,
+    pub file_path: String,
+}
+
+pub fn get_epoch_index_entry(
+    epoch_number: usize,
+    epoch_root: H256,
+    file_path: String,
+) -> EpochIndexEntry {
+    let content_key = epoch_accumulator_content_key(&epoch_root);
+
+    EpochIndexEntry {
+        epoch_number,
+        epoch_root,
+        content_id: content_id(&content_key),
+        content_key,
+        file_path,
+    }
+}
+
+//portal uses the xor of the two ids as the distance
+pub fn distance(node_id: &H256, content_id: &H256) -> U256 {
+    U256::from_big_endian((*node_id ^ *content_id).as_bytes())
+}
+
+pub fn is_within_radius(node_id: &H256, content_id: &H256, radius: &U256) -> bool {
+    distance(node_id, content_id) <= *radius
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_index_entry_is_hex_encoded() {
+        let epoch_root = H256::from_low_u64_be(0x1234);
+        let entry =
+            get_epoch_index_entry(3, epoch_root, String::from("epoch/epoch_accumulator_3.txt"));
+        let json: serde_json::Value = serde_json::to_value(&entry).unwrap();
+
+        assert_eq!(3, json["epoch_number"]);
+        assert_eq!(format!("{:?}", epoch_root), json["epoch_root"]);
+        assert_eq!(
+            format!("0x03{}", &format!("{:?}", epoch_root)[2..]),
+            json["content_key"]
+        );
+        assert_eq!(format!("{:?}", entry.content_id), json["content_id"]);
+        assert_eq!("epoch/epoch_accumulator_3.txt", json["file_path"]);
+    }
+
+    #[test]
+    fn content_within_radius_of_node() {
+        let node_id = H256::from_low_u64_be(0b1100);
+        let content_id = H256::from_low_u64_be(0b1010);
+
+        assert_eq!(U256::from(0b0110), distance(&node_id, &content_id));
+        assert!(is_within_radius(&node_id, &content_id, &U256::from(6)));
+        assert!(!is_within_radius(&node_id, &content_id, &U256::from(5)));
+        assert!(is_within_radius(&node_id, &node_id, &U256::zero()));
+        assert!(is_within_radius(&node_id, &content_id, &U256::MAX));
+    }
+}
@@ -0,0 +1,227 @@
+use crate::proof_vector::{generate_block_proof_vector, BlockProofVector};
+use accumulator_trie::trie::TrieTrait;
+use accumulator_trie::{AccumulatorTrie, AccumulatorTrieTrait};
+use rand::Rng;
+
+//sorted positions of the sampled blocks, every block is taken when the epoch is smaller than the sample
+pub fn sample_leaf_indices<R: Rng>(
+    rng: &mut R,
+    epoch_length: usize,
+    sample_size: usize,
+) -> Vec<usize> {
+    let amount = sample_size.min(epoch_length);
+    let mut indices = rand::seq::index::sample(rng, epoch_length, amount).into_vec();
+    indices.sort_unstable();
+
+    indices
+}
+
+//copies epochs first_epoch..=last_epoch and the master accumulator truncated to last_epoch
+//into the empty fixture trie, proofs are generated against the fixture so they match its root
+pub fn build_fixture<T: TrieTrait, F: TrieTrait, R: Rng>(
+    accumulator_trie: &AccumulatorTrie<T>,
+    fixture_trie: &AccumulatorTrie<F>,
+    first_epoch: usize,
+    last_epoch: usize,
+    sample_size: usize,
+    rng: &mut R,
+) -> Result<Vec<BlockProofVector>, String> {
+    let master_accumulator = accumulator_trie
+        .get_master_accumulator()
+        .map_err(|x| format!("Unable to read the master accumulator: {:?}", x))?;
+    if first_epoch == 0 || first_epoch > last_epoch || last_epoch > master_accumulator.len() {
+        return Err(format!(
+            "Epochs {} to {} are not in the accumulator of {} epochs",
+            first_epoch,
+            last_epoch,
+            master_accumulator.len()
+        ));
+    }
+
+    match fixture_trie.get_master_accumulator() {
+        Ok(x) if x.is_empty() => (),
+        _ => {
+            return Err(String::from(
+                "The fixture folder already holds an accumulator",
+            ))
+        }
+    }
+
+    let truncated_master_accumulator = master_accumulator[..last_epoch].to_vec();
+    let mut result = fixture_trie.store_master_accumulator(&truncated_master_accumulator);
+
+    let mut last_epoch_length = 0;
+    let mut block_numbers = vec![];
+    for epoch_number in first_epoch..=last_epoch {
+        let epoch_accumulator = accumulator_trie
+            .get_epoch_accumulator_by_epoch_number(&epoch_number)
+            .map_err(|x| format!("Unable to read epoch {}: {:?}", epoch_number, x))?;
+        result = result
+            && fixture_trie
+                .store_epoch_accumulator_by_epoch_number(&epoch_number, &epoch_accumulator);
+
+        let first_block_number = (epoch_number as u32 - 1) * accumulator_trie.epoch_size;
+        for index in sample_leaf_indices(rng, epoch_accumulator.len(), sample_size) {
+            block_numbers.push(first_block_number + index as u32);
+        }
+        last_epoch_length = epoch_accumulator.len();
+    }
+
+    //the last epoch may still be in progress, it has no historical root until it is full
+    let completed_epochs = match last_epoch_length == accumulator_trie.epoch_size as usize {
+        true => last_epoch,
+        false => last_epoch - 1,
+    };
+    result = result
+        && fixture_trie
+            .store_historical_master_roots(&truncated_master_accumulator[..completed_epochs]);
+
+    if !result {
+        return Err(String::from("Unable to write the fixture accumulator"));
+    }
+
+    block_numbers
+        .iter()
+        .map(|x| generate_block_proof_vector(fixture_trie, x))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accumulator_storage::accumulator_store::AccumulatorStore;
+    use accumulator_storage::memory_storage::AccumulatorMemoryStorage;
+    use accumulator_trie::epoch_sedes::EpochSede;
+    use accumulator_trie::trie::Trie;
+    use ethereum_types::{H256, U256};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn init_accumulator_trie() -> AccumulatorTrie<Trie<AccumulatorMemoryStorage>> {
+        let mut accumulator_trie = AccumulatorTrie::new(Trie::new(AccumulatorMemoryStorage::new()));
+        accumulator_trie.epoch_size = 4;
+
+        accumulator_trie
+    }
+
+    #[test]
+    fn sample_leaf_indices_is_sorted_and_seedable() {
+        let indices = sample_leaf_indices(&mut StdRng::seed_from_u64(7), 100, 10);
+        assert_eq!(10, indices.len());
+        assert!(indices.windows(2).all(|x| x[0] < x[1]));
+        assert_eq!(
+            indices,
+            sample_leaf_indices(&mut StdRng::seed_from_u64(7), 100, 10)
+        );
+
+        assert_eq!(
+            vec![0, 1, 2],
+            sample_leaf_indices(&mut StdRng::seed_from_u64(7), 3, 10)
+        );
+    }
+
+    //stores epochs of the given lengths, only full epochs get a historical root
+    fn store_epochs(
+        accumulator_trie: &AccumulatorTrie<Trie<AccumulatorMemoryStorage>>,
+        epoch_lengths: &[u64],
+    ) -> Vec<H256> {
+        //creates the master accumulator file like the accumulator run does
+        let mut master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+        for (index, epoch_length) in epoch_lengths.iter().enumerate() {
+            let epoch_number = index as u64 + 1;
+            let epoch_accumulator: Vec<EpochSede> = (0..*epoch_length)
+                .map(|x| {
+                    EpochSede::new(U256::from(x), H256::from_low_u64_be(epoch_number * 10 + x))
+                })
+                .collect();
+            accumulator_trie.store_epoch_accumulator_by_epoch_number(
+                &(epoch_number as usize),
+                &epoch_accumulator,
+            );
+            master_accumulator.push(accumulator_trie.get_tree_root_hash(&epoch_accumulator));
+        }
+        accumulator_trie.store_master_accumulator(&master_accumulator);
+
+        let completed_epochs = epoch_lengths
+            .iter()
+            .take_while(|x| **x == accumulator_trie.epoch_size as u64)
+            .count();
+        accumulator_trie.store_historical_master_roots(&master_accumulator[..completed_epochs]);
+
+        master_accumulator
+    }
+
+    #[test]
+    fn build_fixture_of_epoch_range() {
+        let accumulator_trie = init_accumulator_trie();
+        let master_accumulator = store_epochs(&accumulator_trie, &[4, 4, 4, 4]);
+
+        let fixture_trie = init_accumulator_trie();
+        let proofs = build_fixture(
+            &accumulator_trie,
+            &fixture_trie,
+            2,
+            3,
+            2,
+            &mut StdRng::seed_from_u64(1),
+        )
+        .unwrap();
+
+        let fixture_master_accumulator = fixture_trie.get_master_accumulator().unwrap();
+        assert_eq!(&master_accumulator[..3], &fixture_master_accumulator[..]);
+        assert_eq!(
+            accumulator_trie.get_master_root_by_epoch_number(&3),
+            fixture_trie.get_master_accumulator_root()
+        );
+
+        assert_eq!(4, proofs.len());
+        for proof in proofs.iter() {
+            assert!(proof.block_number >= 4 && proof.block_number < 12);
+            assert_eq!(
+                fixture_trie.get_master_accumulator_root(),
+                Some(proof.master_root)
+            );
+            assert!(proof.verify());
+        }
+
+        //epochs before the range are only kept as roots
+        assert_eq!(
+            vec!["epoch_accumulator_2.txt", "epoch_accumulator_3.txt"],
+            fixture_trie.trie.accumulator_storage.list_epoch_accumulators()
+        );
+    }
+
+    #[test]
+    fn build_fixture_with_partial_last_epoch() {
+        let accumulator_trie = init_accumulator_trie();
+        let master_accumulator = store_epochs(&accumulator_trie, &[4, 4, 2]);
+
+        let fixture_trie = init_accumulator_trie();
+        let proofs = build_fixture(
+            &accumulator_trie,
+            &fixture_trie,
+            2,
+            3,
+            4,
+            &mut StdRng::seed_from_u64(1),
+        )
+        .unwrap();
+
+        //the partial epoch is in the master accumulator but has no historical root
+        assert_eq!(
+            master_accumulator,
+            fixture_trie.get_master_accumulator().unwrap()
+        );
+        assert_eq!(
+            accumulator_trie.get_master_root_by_epoch_number(&2),
+            fixture_trie.get_master_root_by_epoch_number(&2)
+        );
+        assert_eq!(None, fixture_trie.get_master_root_by_epoch_number(&3));
+
+        assert_eq!(6, proofs.len());
+        for proof in proofs.iter() {
+            assert!(proof.block_number >= 4 && proof.block_number < 10);
+            assert!(proof.verify());
+        }
+    }
+}
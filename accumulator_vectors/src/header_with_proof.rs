@@ -0,0 +1,164 @@
+use accumulator_trie::portal::{
+    block_header_content_key, get_accumulator_proof, BlockHeaderProof, BlockHeaderWithProof,
+};
+use accumulator_trie::trie::TrieTrait;
+use accumulator_trie::{AccumulatorTrie, AccumulatorTrieTrait};
+use ethereum_types::H256;
+use serde_derive::Serialize;
+use ssz::Encode;
+use ssz_types::VariableList;
+use tiny_keccak::{Hasher, Keccak};
+
+const MAX_HEADER_LENGTH: usize = 2048;
+
+#[derive(Debug, Serialize)]
+pub struct HeaderWithProofVector {
+    pub block_number: u32,
+    #[serde(serialize_with = "impl_serde::serialize::serialize")]
+    pub content_key: Vec<u8>,
+    #[serde(serialize_with = "impl_serde::serialize::serialize")]
+    pub content_value: Vec<u8>,
+}
+
+//the header is left empty when no rlp is provided, our block source only has hashes
+pub fn generate_header_with_proof<T: TrieTrait>(
+    accumulator_trie: &AccumulatorTrie<T>,
+    block_number: &u32,
+    header_rlp: Option<Vec<u8>>,
+) -> Result<HeaderWithProofVector, String> {
+    let epoch_sede = accumulator_trie
+        .get_epoch_sede_by_block_number(block_number)
+        .ok_or(format!("Block {} is not in the accumulator", block_number))?;
+    let epoch_branch = accumulator_trie
+        .generate_block_proof(block_number)
+        .ok_or(format!(
+            "Unable to generate proof for block {}",
+            block_number
+        ))?;
+
+    let header = header_rlp.unwrap_or_default();
+    if header.len() > MAX_HEADER_LENGTH {
+        return Err(format!(
+            "Header rlp of {} bytes is longer than {}",
+            header.len(),
+            MAX_HEADER_LENGTH
+        ));
+    }
+    if !header.is_empty() && keccak256(&header) != epoch_sede.block_hash() {
+        return Err(format!(
+            "Header rlp does not hash to block hash {:?}",
+            epoch_sede.block_hash()
+        ));
+    }
+
+    let header_with_proof = BlockHeaderWithProof {
+        header: VariableList::from(header),
+        proof: BlockHeaderProof::AccumulatorProof(get_accumulator_proof(
+            &epoch_sede,
+            &epoch_branch,
+        )),
+    };
+
+    Ok(HeaderWithProofVector {
+        block_number: *block_number,
+        content_key: block_header_content_key(&epoch_sede.block_hash()),
+        content_value: header_with_proof.as_ssz_bytes(),
+    })
+}
+
+fn keccak256(bytes: &[u8]) -> H256 {
+    let mut hasher = Keccak::v256();
+    let mut output = [0; 32];
+    hasher.update(bytes);
+    hasher.finalize(&mut output);
+
+    H256::from(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accumulator_storage::memory_storage::AccumulatorMemoryStorage;
+    use accumulator_trie::epoch_sedes::EpochSede;
+    use accumulator_trie::proof::verify_block_hash_in_epoch;
+    use accumulator_trie::trie::Trie;
+    use ethereum_types::U256;
+    use ssz::Decode;
+
+    fn get_header_rlp(block_number: u64) -> Vec<u8> {
+        format!("header of block {}", block_number).into_bytes()
+    }
+
+    //one epoch of 3 blocks whose hashes are the keccak of get_header_rlp
+    fn init_accumulator_trie() -> AccumulatorTrie<Trie<AccumulatorMemoryStorage>> {
+        let mut accumulator_trie = AccumulatorTrie::new(Trie::new(AccumulatorMemoryStorage::new()));
+        accumulator_trie.epoch_size = 4;
+
+        let epoch_accumulator: Vec<EpochSede> = (0..3)
+            .map(|x| EpochSede::new(U256::from(x * 5), keccak256(&get_header_rlp(x))))
+            .collect();
+        let mut master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+        accumulator_trie.store_epoch_accumulator_by_epoch_number(&1, &epoch_accumulator);
+        master_accumulator.push(accumulator_trie.get_tree_root_hash(&epoch_accumulator));
+        accumulator_trie.store_master_accumulator(&master_accumulator);
+
+        accumulator_trie
+    }
+
+    #[test]
+    fn header_with_proof_verifies_against_epoch_root() {
+        let accumulator_trie = init_accumulator_trie();
+        let vector =
+            generate_header_with_proof(&accumulator_trie, &2, Some(get_header_rlp(2))).unwrap();
+
+        let block_hash = keccak256(&get_header_rlp(2));
+        assert_eq!(block_header_content_key(&block_hash), vector.content_key);
+
+        let decoded = BlockHeaderWithProof::from_ssz_bytes(&vector.content_value).unwrap();
+        assert_eq!(get_header_rlp(2), decoded.header.to_vec());
+        let proof = match decoded.proof {
+            BlockHeaderProof::AccumulatorProof(x) => x,
+            BlockHeaderProof::None => panic!("Expected an accumulator proof"),
+        };
+        let epoch_root = accumulator_trie.get_master_accumulator().unwrap()[0];
+        assert!(verify_block_hash_in_epoch(
+            block_hash, 2, &proof, epoch_root
+        ));
+    }
+
+    #[test]
+    fn header_is_left_empty_without_rlp() {
+        let accumulator_trie = init_accumulator_trie();
+        let vector = generate_header_with_proof(&accumulator_trie, &0, None).unwrap();
+
+        let decoded = BlockHeaderWithProof::from_ssz_bytes(&vector.content_value).unwrap();
+        assert!(decoded.header.is_empty());
+    }
+
+    #[test]
+    fn header_rlp_must_hash_to_block_hash() {
+        let accumulator_trie = init_accumulator_trie();
+
+        let result = generate_header_with_proof(&accumulator_trie, &2, Some(get_header_rlp(1)));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn header_rlp_longer_than_limit_fails() {
+        let accumulator_trie = init_accumulator_trie();
+        let header_rlp = vec![0; MAX_HEADER_LENGTH + 1];
+
+        let result = generate_header_with_proof(&accumulator_trie, &2, Some(header_rlp));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn missing_block_or_epoch_fails() {
+        let accumulator_trie = init_accumulator_trie();
+
+        //past the last block of the only epoch
+        assert!(generate_header_with_proof(&accumulator_trie, &3, None).is_err());
+        //in an epoch that does not exist yet
+        assert!(generate_header_with_proof(&accumulator_trie, &9, None).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use accumulator_trie::epoch_sedes::EpochSede;
+use accumulator_trie::master_accumulator::MasterAccumulator;
+use accumulator_trie::trie::TrieTrait;
+use accumulator_trie::{AccumulatorTrie, AccumulatorTrieTrait};
+use ssz::Decode;
+use ssz_types::{typenum, VariableList};
+use typenum::U2048;
+
+pub fn decode_master_accumulator(encoded: &[u8]) -> Result<MasterAccumulator, String> {
+    MasterAccumulator::from_ssz_bytes(encoded)
+        .map_err(|x| format!("Unable to decode master accumulator: {:?}", x))
+}
+
+pub fn decode_epoch_accumulator(encoded: &[u8]) -> Result<Vec<EpochSede>, String> {
+    <VariableList<EpochSede, U2048>>::from_ssz_bytes(encoded)
+        .map(|x| x.to_vec())
+        .map_err(|x| format!("Unable to decode epoch accumulator: {:?}", x))
+}
+
+//every epoch accumulator has to hash to one of the completed epochs of the master accumulator
+//nothing is written unless all of them match, returns the imported epoch numbers
+pub fn import_accumulator<T: TrieTrait>(
+    accumulator_trie: &AccumulatorTrie<T>,
+    master_accumulator: &MasterAccumulator,
+    epoch_accumulators: &[Vec<EpochSede>],
+) -> Result<Vec<usize>, String> {
+    let mut epochs = vec![];
+    for (i, epoch_accumulator) in epoch_accumulators.iter().enumerate() {
+        let epoch_root = accumulator_trie.get_tree_root_hash(epoch_accumulator);
+        let epoch_number = master_accumulator
+            .historical_epochs
+            .iter()
+            .position(|x| *x == epoch_root)
+            .map(|x| x + 1)
+            .ok_or(format!(
+                "Epoch file {} with root {:?} is not in the master accumulator",
+                i, epoch_root
+            ))?;
+
+        if epoch_accumulator.len() as u32 != accumulator_trie.epoch_size {
+            return Err(format!(
+                "Epoch {} has {} blocks instead of {}",
+                epoch_number,
+                epoch_accumulator.len(),
+                accumulator_trie.epoch_size
+            ));
+        }
+
+        epochs.push((epoch_number, epoch_accumulator));
+    }
+
+    //a full last epoch is the one a run resumes from, so its blocks cannot be left out
+    let last_epoch_number = master_accumulator.historical_epochs.len();
+    let is_last_epoch_imported = epochs.iter().any(|x| x.0 == last_epoch_number);
+    if master_accumulator.current_epoch.is_empty()
+        && last_epoch_number > 0
+        && !is_last_epoch_imported
+    {
+        return Err(format!(
+            "Epoch {} is the last epoch of the master accumulator, its epoch file is needed to resume",
+            last_epoch_number
+        ));
+    }
+
+    if !accumulator_trie.import_master_accumulator(master_accumulator) {
+        return Err(String::from(
+            "Unable to import the master accumulator, it may already exist",
+        ));
+    }
+
+    for (epoch_number, epoch_accumulator) in epochs.iter() {
+        if !accumulator_trie
+            .store_epoch_accumulator_by_epoch_number(epoch_number, epoch_accumulator)
+        {
+            return Err(format!("Unable to store epoch {}", epoch_number));
+        }
+    }
+
+    //the remaining completed epochs get an empty file, like the ones a run creates
+    for epoch_number in 1..=last_epoch_number {
+        if !epochs.iter().any(|x| x.0 == epoch_number) {
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&epoch_number)
+                .map_err(|_| format!("Unable to create epoch {}", epoch_number))?;
+        }
+    }
+
+    Ok(epochs.iter().map(|x| x.0).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accumulator_storage::accumulator_storage::AccumulatorFileStorage;
+    use accumulator_storage::accumulator_store::AccumulatorStore;
+    use accumulator_storage::memory_storage::AccumulatorMemoryStorage;
+    use accumulator_trie::trie::Trie;
+    use ethereum_types::{H256, U256};
+    use ssz::Encode;
+
+    fn get_epoch_accumulator(epoch_size: u64, seed: u64) -> Vec<EpochSede> {
+        (0..epoch_size)
+            .map(|x| EpochSede::new(U256::from(x + seed), H256::from_low_u64_be(x * seed + 1)))
+            .collect()
+    }
+
+    fn init_accumulator_trie() -> AccumulatorTrie<Trie<AccumulatorMemoryStorage>> {
+        let mut accumulator_trie = AccumulatorTrie::new(Trie::new(AccumulatorMemoryStorage::new()));
+        accumulator_trie.epoch_size = 4;
+
+        accumulator_trie
+    }
+
+    #[test]
+    fn import_accumulator_with_epoch_files() {
+        let accumulator_trie = init_accumulator_trie();
+        let epoch_1 = get_epoch_accumulator(4, 1);
+        let epoch_2 = get_epoch_accumulator(4, 2);
+        let current_epoch = get_epoch_accumulator(2, 3);
+
+        let master_accumulator = MasterAccumulator::new(
+            vec![
+                accumulator_trie.get_tree_root_hash(&epoch_1),
+                accumulator_trie.get_tree_root_hash(&epoch_2),
+            ],
+            current_epoch.clone(),
+        );
+        let decoded = decode_master_accumulator(&master_accumulator.as_ssz_bytes()).unwrap();
+
+        //a foreign epoch fails before anything is written
+        let result =
+            import_accumulator(&accumulator_trie, &decoded, &[get_epoch_accumulator(4, 9)]);
+        assert!(result.is_err());
+        assert!(accumulator_trie
+            .get_master_accumulator()
+            .unwrap()
+            .is_empty());
+
+        let result =
+            import_accumulator(&accumulator_trie, &decoded, std::slice::from_ref(&epoch_2));
+        assert_eq!(Ok(vec![2]), result);
+
+        assert_eq!(3, accumulator_trie.get_master_accumulator().unwrap().len());
+        assert_eq!(
+            epoch_2,
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&2)
+                .unwrap()
+        );
+        assert_eq!(
+            current_epoch,
+            accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&3)
+                .unwrap()
+        );
+        assert_eq!(
+            Some(master_accumulator),
+            accumulator_trie.export_master_accumulator()
+        );
+
+        //the existing accumulator is never overwritten
+        let result = import_accumulator(&accumulator_trie, &decoded, &[epoch_1]);
+        assert!(result.is_err());
+    }
+
+    //removes the folders of the file storage when the test ends, even if it fails
+    struct TempDir(std::path::PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn init_file_accumulator_trie(
+        temp_dir: &TempDir,
+    ) -> AccumulatorTrie<Trie<AccumulatorFileStorage>> {
+        let base_path = temp_dir.0.to_str().unwrap();
+        let accumulator_storage = AccumulatorFileStorage::new(
+            format!("{}/master", base_path),
+            format!("{}/epoch", base_path),
+        );
+        let mut accumulator_trie = AccumulatorTrie::new(Trie::new(accumulator_storage));
+        accumulator_trie.epoch_size = 4;
+
+        accumulator_trie
+    }
+
+    #[test]
+    fn import_accumulator_with_full_last_epoch_can_be_resumed() {
+        let temp_dir = TempDir(
+            std::env::temp_dir().join(format!("accumulator_import_{}", std::process::id())),
+        );
+        let accumulator_trie = init_file_accumulator_trie(&temp_dir);
+        let epoch_1 = get_epoch_accumulator(4, 1);
+        let epoch_2 = get_epoch_accumulator(4, 2);
+
+        //an export whose last epoch is full has no current epoch
+        let master_accumulator = MasterAccumulator::new(
+            vec![
+                accumulator_trie.get_tree_root_hash(&epoch_1),
+                accumulator_trie.get_tree_root_hash(&epoch_2),
+            ],
+            vec![],
+        );
+
+        //the last epoch is needed to resume, so nothing is written without it
+        let result = import_accumulator(&accumulator_trie, &master_accumulator, &[epoch_1]);
+        assert!(result.is_err());
+        assert!(accumulator_trie
+            .trie
+            .accumulator_storage
+            .list_epoch_accumulators()
+            .is_empty());
+
+        let result = import_accumulator(
+            &accumulator_trie,
+            &master_accumulator,
+            std::slice::from_ref(&epoch_2),
+        );
+        assert_eq!(Ok(vec![2]), result);
+        assert_eq!(
+            vec!["epoch_accumulator_1.txt", "epoch_accumulator_2.txt"],
+            accumulator_trie
+                .trie
+                .accumulator_storage
+                .list_epoch_accumulators()
+        );
+
+        //the same check the accumulator runs on start
+        let master = accumulator_trie.get_master_accumulator().unwrap();
+        assert_eq!(
+            Some(epoch_2),
+            accumulator_trie.get_current_epoch_accumulator(&master)
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use accumulator_trie::epoch_sedes::EpochSede;
+use ethereum_types::H256;
+use serde_derive::{Deserialize, Serialize};
+use ssz::Encode;
+use ssz_types::{typenum, VariableList};
+use typenum::{U16777216, U2048};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EpochSedeEntry {
+    pub index: usize,
+    pub block_number: u32,
+    #[serde(flatten)]
+    pub epoch_sede: EpochSede,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MasterEntry {
+    pub index: usize,
+    pub epoch_number: usize,
+    pub epoch_root: H256,
+}
+
+//block numbers are counted from the first block of epoch 1
+pub fn epoch_accumulator_to_json(
+    epoch_number: usize,
+    epoch_size: u32,
+    epoch_accumulator: &[EpochSede],
+) -> Vec<EpochSedeEntry> {
+    let first_block_number = (epoch_number as u32 - 1) * epoch_size;
+
+    epoch_accumulator
+        .iter()
+        .enumerate()
+        .map(|(i, x)| EpochSedeEntry {
+            index: i,
+            block_number: first_block_number + i as u32,
+            epoch_sede: *x,
+        })
+        .collect()
+}
+
+//entries have to be in order, the ssz list has no room for gaps
+pub fn epoch_accumulator_from_json(entries: &[EpochSedeEntry]) -> Result<Vec<u8>, String> {
+    check_indices(entries.iter().map(|x| x.index))?;
+
+    let epoch_accumulator: Vec<EpochSede> = entries.iter().map(|x| x.epoch_sede).collect();
+    let epoch_var_list: VariableList<_, U2048> = VariableList::new(epoch_accumulator)
+        .map_err(|_| String::from("Epoch accumulator has too many entries"))?;
+
+    Ok(epoch_var_list.as_ssz_bytes())
+}
+
+pub fn master_accumulator_to_json(master_accumulator: &[H256]) -> Vec<MasterEntry> {
+    master_accumulator
+        .iter()
+        .enumerate()
+        .map(|(i, x)| MasterEntry {
+            index: i,
+            epoch_number: i + 1,
+            epoch_root: *x,
+        })
+        .collect()
+}
+
+pub fn master_accumulator_from_json(entries: &[MasterEntry]) -> Result<Vec<u8>, String> {
+    check_indices(entries.iter().map(|x| x.index))?;
+
+    let master_accumulator: Vec<H256> = entries.iter().map(|x| x.epoch_root).collect();
+    let master_var_list: VariableList<_, U16777216> = VariableList::new(master_accumulator)
+        .map_err(|_| String::from("Master accumulator has too many entries"))?;
+
+    Ok(master_var_list.as_ssz_bytes())
+}
+
+fn check_indices(indices: impl Iterator<Item = usize>) -> Result<(), String> {
+    for (expected, index) in indices.enumerate() {
+        if expected != index {
+            return Err(format!("Expected index {} but found {}", expected, index));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::U256;
+    use ssz::Decode;
+
+    #[test]
+    fn epoch_accumulator_json_round_trip() {
+        let epoch_accumulator: Vec<EpochSede> = (0..3)
+            .map(|x| EpochSede::new(U256::from(x * 100), H256::from_low_u64_be(x + 1)))
+            .collect();
+
+        let entries = epoch_accumulator_to_json(2, 2048, &epoch_accumulator);
+        assert_eq!(2049, entries[1].block_number);
+
+        let json = serde_json::to_string(&entries).unwrap();
+        let json_value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!("0x64", json_value[1]["total_difficulty"]);
+        assert_eq!(
+            format!("{:?}", H256::from_low_u64_be(2)),
+            json_value[1]["block_hash"]
+        );
+
+        let decoded_entries: Vec<EpochSedeEntry> = serde_json::from_str(&json).unwrap();
+        let encoded = epoch_accumulator_from_json(&decoded_entries).unwrap();
+        let decoded = <VariableList<EpochSede, U2048>>::from_ssz_bytes(&encoded).unwrap();
+        assert_eq!(epoch_accumulator, decoded.to_vec());
+    }
+
+    #[test]
+    fn master_accumulator_json_round_trip() {
+        let master_accumulator: Vec<H256> = (0..3).map(|x| H256::from_low_u64_be(x + 1)).collect();
+
+        let entries = master_accumulator_to_json(&master_accumulator);
+        let json = serde_json::to_string(&entries).unwrap();
+        let decoded_entries: Vec<MasterEntry> = serde_json::from_str(&json).unwrap();
+
+        let encoded = master_accumulator_from_json(&decoded_entries).unwrap();
+        let decoded = <VariableList<H256, U16777216>>::from_ssz_bytes(&encoded).unwrap();
+        assert_eq!(master_accumulator, decoded.to_vec());
+    }
+
+    #[test]
+    fn from_json_fails_on_missing_index() {
+        let mut entries = master_accumulator_to_json(&[H256::zero(), H256::zero()]);
+        entries.remove(0);
+
+        assert!(master_accumulator_from_json(&entries).is_err());
+    }
+}
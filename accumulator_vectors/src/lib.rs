@@ -0,0 +1,530 @@
+pub mod content_index;
+pub mod fixture;
+pub mod header_with_proof;
+pub mod importer;
+pub mod json_convert;
+pub mod proof_vector;
+pub mod ssz_static;
+pub mod tampered;
+
+use accumulator_storage::accumulator_storage::AccumulatorFileStorage;
+use accumulator_storage::sled_storage::AccumulatorSledStorage;
+use accumulator_trie::trie::{Trie, TrieTrait};
+use accumulator_trie::{AccumulatorTrie, AccumulatorTrieTrait};
+use config::{Config, StorageBackend};
+use content_index::{get_epoch_index_entry, is_within_radius, EpochIndexEntry};
+use ethereum_types::{H256, U256};
+use fixture::build_fixture;
+use header_with_proof::generate_header_with_proof;
+use importer::{decode_epoch_accumulator, decode_master_accumulator, import_accumulator};
+use json_convert::{
+    epoch_accumulator_from_json, epoch_accumulator_to_json, master_accumulator_from_json,
+    master_accumulator_to_json,
+};
+use proof_vector::{
+    generate_block_proof_vector, generate_epoch_proof_vector, generate_multiproof_vector,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+use ssz::Encode;
+use ssz_static::{write_ssz_static_case, EpochSedeValue};
+use ssz_types::{typenum, VariableList};
+use std::path::PathBuf;
+use std::str::FromStr;
+use tampered::{tamper_block_proof, tamper_epoch_proof};
+use typenum::{U16777216, U2048};
+
+pub fn run(config: Config, args: Vec<String>) {
+    match config.storage_backend {
+        StorageBackend::File => {
+            let accumulator_storage = AccumulatorFileStorage::new(
+                config.master_accumulator_file_path.clone(),
+                config.epoch_accumulator_file_path.clone(),
+            )
+            .with_snappy_compression(config.snappy_compression);
+            let accumulator_trie = AccumulatorTrie::new(Trie::new(accumulator_storage));
+            recover_accumulators(&accumulator_trie);
+
+            //these copy the epoch files, so they are only available for the file storage
+            match args.first().map(|x| x.as_str()) {
+                Some("epoch-index") => {
+                    let index = get_epoch_index(&accumulator_trie);
+                    write_vector_file(
+                        &config.test_vector_file_path,
+                        "",
+                        "epoch_accumulator_index.json",
+                        &index,
+                    );
+                }
+                Some("radius-export") => {
+                    let node_id = H256::from_str(
+                        args.get(1)
+                            .expect("Please provide a node id")
+                            .trim_start_matches("0x"),
+                    )
+                    .expect("Node id is not a valid 32 byte hex");
+                    let radius = parse_radius(args.get(2));
+                    let output_path = args.get(3).cloned().unwrap_or(format!(
+                        "{}/radius_export/{:x}",
+                        config.test_vector_file_path, node_id
+                    ));
+
+                    let index: Vec<EpochIndexEntry> = get_epoch_index(&accumulator_trie)
+                        .into_iter()
+                        .filter(|x| is_within_radius(&node_id, &x.content_id, &radius))
+                        .map(|mut x| {
+                            let mut path = PathBuf::from(&output_path);
+                            std::fs::create_dir_all(&path).unwrap();
+                            path.push(PathBuf::from(&x.file_path).file_name().unwrap());
+
+                            std::fs::copy(&x.file_path, &path).unwrap();
+                            x.file_path = path.to_string_lossy().into_owned();
+                            x
+                        })
+                        .collect();
+
+                    println!("{} epochs are within the radius", index.len());
+                    write_vector_file(&output_path, "", "content_keys.json", &index);
+                }
+                _ => run_command(&config, &args, accumulator_trie),
+            }
+        }
+        StorageBackend::Sled => {
+            let accumulator_storage = AccumulatorSledStorage::open(&config.sled_db_path)
+                .expect("Unable to open the accumulator database");
+            let accumulator_trie = AccumulatorTrie::new(Trie::new(accumulator_storage));
+            recover_accumulators(&accumulator_trie);
+
+            match args.first().map(|x| x.as_str()) {
+                Some("epoch-index") | Some("radius-export") => {
+                    println!("The epoch index is only written for the file storage")
+                }
+                _ => run_command(&config, &args, accumulator_trie),
+            }
+        }
+    }
+}
+
+//a commit that was interrupted is finished first, so no vector is generated from half written files
+fn recover_accumulators<T: TrieTrait>(accumulator_trie: &AccumulatorTrie<T>) {
+    if !accumulator_trie.recover_accumulators() {
+        panic!("Unable to recover the accumulators from the journal");
+    }
+}
+
+fn run_command<T: TrieTrait>(
+    config: &Config,
+    args: &[String],
+    accumulator_trie: AccumulatorTrie<T>,
+) {
+    match args.first().map(|x| x.as_str()) {
+        Some("proof") => {
+            let block_number = parse_block_number(args.get(1));
+            write_block_proof_vector(
+                &config.test_vector_file_path,
+                &accumulator_trie,
+                &block_number,
+            );
+        }
+        Some("epoch-proof") => {
+            let epoch_number = parse_epoch_number(args.get(1));
+            write_epoch_proof_vector(
+                &config.test_vector_file_path,
+                &accumulator_trie,
+                &epoch_number,
+            );
+        }
+        Some("multiproof") => {
+            let first_block = parse_block_number(args.get(1));
+            let last_block = parse_block_number(args.get(2));
+
+            match generate_multiproof_vector(&accumulator_trie, &first_block, &last_block) {
+                Ok(vector) => {
+                    let file_name = format!("blocks_{}_{}.json", first_block, last_block);
+                    write_vector_file(
+                        &config.test_vector_file_path,
+                        "multiproof",
+                        &file_name,
+                        &vector,
+                    );
+                }
+                Err(x) => println!("{}", x),
+            }
+        }
+        Some("header-with-proof") => {
+            let block_number = parse_block_number(args.get(1));
+            let header_rlp = args
+                .get(2)
+                .map(|x| impl_serde::serialize::from_hex(x).expect("Header rlp is not valid hex"));
+
+            match generate_header_with_proof(&accumulator_trie, &block_number, header_rlp) {
+                Ok(vector) => {
+                    let file_name = format!("block_{}.json", block_number);
+                    write_vector_file(
+                        &config.test_vector_file_path,
+                        "header_with_proof",
+                        &file_name,
+                        &vector,
+                    );
+                    write_block_proof_vector(
+                        &config.test_vector_file_path,
+                        &accumulator_trie,
+                        &block_number,
+                    );
+                }
+                Err(x) => println!("{}", x),
+            }
+        }
+        Some("tampered-block-proof") => {
+            let block_number = parse_block_number(args.get(1));
+            let (epoch_number, index) = accumulator_trie.get_epoch_number_and_index(&block_number);
+
+            let epoch_sede = accumulator_trie.get_epoch_sede_by_block_number(&block_number);
+            let branch = accumulator_trie.generate_block_proof(&block_number);
+            match (epoch_sede, branch) {
+                (Some(epoch_sede), Some(branch)) => {
+                    let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+                    let epoch_root = master_accumulator[epoch_number - 1];
+
+                    let cases = tamper_block_proof(epoch_sede, index, &branch, epoch_root);
+                    let file_name = format!("block_{}.json", block_number);
+                    write_vector_file(
+                        &config.test_vector_file_path,
+                        "tampered_block_proof",
+                        &file_name,
+                        &cases,
+                    );
+                    write_block_proof_vector(
+                        &config.test_vector_file_path,
+                        &accumulator_trie,
+                        &block_number,
+                    );
+                }
+                _ => println!("Block {} is not in the accumulator", block_number),
+            }
+        }
+        Some("tampered-epoch-proof") => {
+            let epoch_number = parse_epoch_number(args.get(1));
+
+            match accumulator_trie.generate_epoch_proof(&epoch_number) {
+                Some(branch) => {
+                    let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+                    let epoch_root = master_accumulator[epoch_number - 1];
+                    let master_root = accumulator_trie.get_master_root_hash(&master_accumulator);
+
+                    let cases =
+                        tamper_epoch_proof(epoch_root, epoch_number - 1, &branch, master_root);
+                    let file_name = format!("epoch_{}.json", epoch_number);
+                    write_vector_file(
+                        &config.test_vector_file_path,
+                        "tampered_epoch_proof",
+                        &file_name,
+                        &cases,
+                    );
+                    write_epoch_proof_vector(
+                        &config.test_vector_file_path,
+                        &accumulator_trie,
+                        &epoch_number,
+                    );
+                }
+                None => println!("Epoch {} is not in the accumulator", epoch_number),
+            }
+        }
+        Some("ssz-static") => {
+            let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+
+            for (i, epoch_hash) in master_accumulator.iter().enumerate() {
+                let epoch_number = i + 1;
+                let epoch_accumulator = accumulator_trie
+                    .get_epoch_accumulator_by_epoch_number(&epoch_number)
+                    .unwrap();
+
+                let value: Vec<EpochSedeValue> = epoch_accumulator
+                    .iter()
+                    .map(|x| EpochSedeValue {
+                        block_hash: x.block_hash(),
+                        total_difficulty: x.total_difficulty().to_string(),
+                    })
+                    .collect();
+                let epoch_var_list: VariableList<_, U2048> = VariableList::from(epoch_accumulator);
+
+                let case_path = write_ssz_static_case(
+                    &config.test_vector_file_path,
+                    "EpochAccumulator",
+                    &format!("case_{}", epoch_number),
+                    &epoch_var_list.as_ssz_bytes(),
+                    &value,
+                    *epoch_hash,
+                );
+                println!("Epoch {} written to {:?}", epoch_number, case_path);
+            }
+
+            let master_root = accumulator_trie.get_master_root_hash(&master_accumulator);
+            let master_var_list: VariableList<_, U16777216> =
+                VariableList::from(master_accumulator.clone());
+
+            let case_path = write_ssz_static_case(
+                &config.test_vector_file_path,
+                "MasterAccumulator",
+                "case_0",
+                &master_var_list.as_ssz_bytes(),
+                &master_accumulator,
+                master_root,
+            );
+            println!("Master accumulator written to {:?}", case_path);
+        }
+        Some("master-accumulator") => match accumulator_trie.export_master_accumulator() {
+            Some(master_accumulator) => {
+                let mut path = PathBuf::from(&config.test_vector_file_path);
+                std::fs::create_dir_all(&path).unwrap();
+                path.push("master_accumulator.ssz");
+
+                std::fs::write(&path, master_accumulator.as_ssz_bytes()).unwrap();
+                println!("Master accumulator written to {:?}", path);
+            }
+            None => println!("Unable to read the master accumulator"),
+        },
+        Some("epoch-to-json") => {
+            let epoch_number = parse_epoch_number(args.get(1));
+            let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+            if epoch_number == 0 || epoch_number > master_accumulator.len() {
+                println!("Epoch {} is not in the accumulator", epoch_number);
+                return;
+            }
+
+            let epoch_accumulator = accumulator_trie
+                .get_epoch_accumulator_by_epoch_number(&epoch_number)
+                .unwrap();
+            let entries = epoch_accumulator_to_json(
+                epoch_number,
+                accumulator_trie.epoch_size,
+                &epoch_accumulator,
+            );
+
+            let file_name = format!("epoch_accumulator_{}.json", epoch_number);
+            write_vector_file(&config.test_vector_file_path, "json", &file_name, &entries);
+        }
+        Some("master-to-json") => {
+            let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+            let entries = master_accumulator_to_json(&master_accumulator);
+
+            write_vector_file(
+                &config.test_vector_file_path,
+                "json",
+                "master_accumulator.json",
+                &entries,
+            );
+        }
+        Some("epoch-from-json") => {
+            let content = read_json_file(args.get(1));
+            let encoded = serde_json::from_str(&content)
+                .map_err(|x| format!("JSON was not well-formatted: {}", x))
+                .and_then(|x: Vec<_>| epoch_accumulator_from_json(&x));
+
+            write_ssz_file(args.get(2), encoded);
+        }
+        Some("master-from-json") => {
+            let content = read_json_file(args.get(1));
+            let encoded = serde_json::from_str(&content)
+                .map_err(|x| format!("JSON was not well-formatted: {}", x))
+                .and_then(|x: Vec<_>| master_accumulator_from_json(&x));
+
+            write_ssz_file(args.get(2), encoded);
+        }
+        Some("import") => {
+            let master_file = args
+                .get(1)
+                .expect("Please provide a master accumulator file");
+            let master_accumulator =
+                decode_master_accumulator(&std::fs::read(master_file).expect("Unable to read"));
+
+            //any further arguments are epoch accumulator files
+            let epoch_accumulators: Result<Vec<_>, String> = args[2..]
+                .iter()
+                .map(|x| decode_epoch_accumulator(&std::fs::read(x).expect("Unable to read")))
+                .collect();
+
+            let result = master_accumulator.and_then(|master_accumulator| {
+                import_accumulator(&accumulator_trie, &master_accumulator, &epoch_accumulators?)
+            });
+            match result {
+                Ok(x) => println!("Imported master accumulator with epochs {:?}", x),
+                Err(x) => println!("{}", x),
+            }
+        }
+        Some("fixture") => {
+            let first_epoch = parse_epoch_number(args.get(1));
+            let last_epoch = parse_epoch_number(args.get(2));
+            let sample_size: usize = args
+                .get(3)
+                .expect("Please provide a sample size")
+                .parse()
+                .expect("Sample size is not a valid number");
+            //the seed is printed so that the same fixture can be generated again
+            let seed: u64 = args
+                .get(4)
+                .map(|x| x.parse().expect("Seed is not a valid number"))
+                .unwrap_or_else(rand::random);
+            println!("Sampling blocks with seed {}", seed);
+
+            let fixture_path = format!(
+                "{}/fixture/epochs_{}_{}",
+                config.test_vector_file_path, first_epoch, last_epoch
+            );
+            let fixture_storage = AccumulatorFileStorage::new(
+                format!("{}/master", fixture_path),
+                format!("{}/epoch", fixture_path),
+            );
+            let mut fixture_trie = AccumulatorTrie::new(Trie::new(fixture_storage));
+            fixture_trie.epoch_size = accumulator_trie.epoch_size;
+
+            let result = build_fixture(
+                &accumulator_trie,
+                &fixture_trie,
+                first_epoch,
+                last_epoch,
+                sample_size,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            match result {
+                Ok(proofs) => {
+                    for proof in proofs.iter() {
+                        let file_name = format!("block_{}.json", proof.block_number);
+                        write_vector_file(&fixture_path, "proof", &file_name, proof);
+                    }
+                }
+                Err(x) => println!("{}", x),
+            }
+        }
+        _ => print_usage(),
+    }
+}
+
+fn print_usage() {
+    println!("Usage: accumulator_vectors <command>");
+    println!("  proof <block_number>");
+    println!("  epoch-proof <epoch_number>");
+    println!("  multiproof <first_block_number> <last_block_number>");
+    println!("  header-with-proof <block_number> [header_rlp_hex]");
+    println!("  tampered-block-proof <block_number>");
+    println!("  tampered-epoch-proof <epoch_number>");
+    println!("  ssz-static");
+    println!("  master-accumulator");
+    println!("  epoch-index");
+    println!("  radius-export <node_id> <radius> [output_path]");
+    println!("  epoch-to-json <epoch_number>");
+    println!("  master-to-json");
+    println!("  epoch-from-json <json_file> <ssz_file>");
+    println!("  master-from-json <json_file> <ssz_file>");
+    println!("  import <master_accumulator_file> [epoch_accumulator_file ...]");
+    println!("  fixture <first_epoch> <last_epoch> <sample_size> [seed]");
+}
+
+fn read_json_file(arg: Option<&String>) -> String {
+    let path = arg.expect("Please provide a json file");
+    std::fs::read_to_string(path).expect("Unable to read the json file")
+}
+
+//the ssz file is written raw, it is not placed in the accumulator folders
+fn write_ssz_file(arg: Option<&String>, encoded: Result<Vec<u8>, String>) {
+    let path = arg.expect("Please provide an ssz file");
+    match encoded {
+        Ok(x) => {
+            std::fs::write(path, x).expect("Unable to write the ssz file");
+            println!("SSZ written to {:?}", path);
+        }
+        Err(x) => println!("{}", x),
+    }
+}
+
+//the radius is either 0x prefixed hex or decimal
+fn parse_radius(arg: Option<&String>) -> U256 {
+    let radius = arg.expect("Please provide a radius");
+    let parsed = match radius.strip_prefix("0x") {
+        Some(x) => U256::from_str(x).ok(),
+        None => U256::from_dec_str(radius).ok(),
+    };
+
+    parsed.expect("Radius is not a valid number")
+}
+
+fn parse_block_number(arg: Option<&String>) -> u32 {
+    arg.expect("Please provide a block number")
+        .parse()
+        .expect("Block number is not a valid number")
+}
+
+fn parse_epoch_number(arg: Option<&String>) -> usize {
+    arg.expect("Please provide an epoch number")
+        .parse()
+        .expect("Epoch number is not a valid number")
+}
+
+//only completed epochs are indexed since the root of the partial epoch still changes
+fn get_epoch_index(
+    accumulator_trie: &AccumulatorTrie<Trie<AccumulatorFileStorage>>,
+) -> Vec<EpochIndexEntry> {
+    let completed_epochs = accumulator_trie
+        .export_master_accumulator()
+        .expect("Unable to read the master accumulator")
+        .historical_epochs;
+
+    completed_epochs
+        .iter()
+        .enumerate()
+        .map(|(i, epoch_root)| {
+            let epoch_number = i + 1;
+            let accumulator_storage = &accumulator_trie.trie.accumulator_storage;
+            let file_path = accumulator_storage.concat_file_path(
+                &accumulator_storage.epoch_file_path,
+                &accumulator_trie
+                    .trie
+                    .get_file_name_from_epoch_number(&epoch_number),
+            );
+
+            get_epoch_index_entry(epoch_number, *epoch_root, file_path)
+        })
+        .collect()
+}
+
+//every generated block proof also gets a readable vector under proof/
+fn write_block_proof_vector<T: TrieTrait>(
+    base_path: &str,
+    accumulator_trie: &AccumulatorTrie<T>,
+    block_number: &u32,
+) {
+    match generate_block_proof_vector(accumulator_trie, block_number) {
+        Ok(vector) => {
+            let file_name = format!("block_{}.json", block_number);
+            write_vector_file(base_path, "proof", &file_name, &vector);
+        }
+        Err(x) => println!("{}", x),
+    }
+}
+
+//every generated epoch proof also gets a readable vector under epoch_proof/
+fn write_epoch_proof_vector<T: TrieTrait>(
+    base_path: &str,
+    accumulator_trie: &AccumulatorTrie<T>,
+    epoch_number: &usize,
+) {
+    match generate_epoch_proof_vector(accumulator_trie, epoch_number) {
+        Ok(vector) => {
+            let file_name = format!("epoch_{}.json", epoch_number);
+            write_vector_file(base_path, "epoch_proof", &file_name, &vector);
+        }
+        Err(x) => println!("{}", x),
+    }
+}
+
+fn write_vector_file<V: Serialize>(base_path: &str, sub_path: &str, file_name: &str, vector: &V) {
+    let mut path = PathBuf::from(base_path);
+    path.push(sub_path);
+    std::fs::create_dir_all(&path).unwrap();
+    path.push(file_name);
+
+    let content = serde_json::to_string_pretty(vector).unwrap();
+    std::fs::write(&path, content).unwrap();
+    println!("Test vector written to {:?}", path);
+}
@@ -0,0 +1,9 @@
+use config::Config;
+
+fn main() {
+    let config_file_name = "config.json";
+
+    let config = Config::new(config_file_name);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    accumulator_vectors::run(config, args);
+}
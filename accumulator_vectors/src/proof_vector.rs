@@ -0,0 +1,315 @@
+use accumulator_trie::epoch_sedes::EpochSede;
+use accumulator_trie::proof::{
+    verify_block_in_epoch, verify_epoch_in_master, verify_epoch_multiproof, EpochMultiproof,
+};
+use accumulator_trie::trie::TrieTrait;
+use accumulator_trie::{AccumulatorTrie, AccumulatorTrieTrait};
+use ethereum_types::{H256, U256};
+use serde_derive::Serialize;
+
+//proves a block up to the master root in two steps:
+//branch from the epoch_sede to the epoch root and master_branch from the epoch root to the master root
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockProofVector {
+    pub block_number: u32,
+    pub block_hash: H256,
+    pub total_difficulty: U256,
+    //position of the epoch in the master accumulator
+    pub epoch_index: usize,
+    //position of the block in its epoch accumulator
+    pub leaf_index: usize,
+    pub branch: Vec<H256>,
+    pub master_branch: Vec<H256>,
+    pub epoch_root: H256,
+    pub master_root: H256,
+}
+
+impl BlockProofVector {
+    pub fn verify(&self) -> bool {
+        let leaf = EpochSede::new(self.total_difficulty, self.block_hash);
+
+        verify_block_in_epoch(leaf, self.leaf_index, &self.branch, self.epoch_root)
+            && verify_epoch_in_master(
+                self.epoch_root,
+                self.epoch_index,
+                &self.master_branch,
+                self.master_root,
+            )
+    }
+}
+
+pub fn generate_block_proof_vector<T: TrieTrait>(
+    accumulator_trie: &AccumulatorTrie<T>,
+    block_number: &u32,
+) -> Result<BlockProofVector, String> {
+    let (epoch_number, leaf_index) = accumulator_trie.get_epoch_number_and_index(block_number);
+
+    let epoch_sede = accumulator_trie
+        .get_epoch_sede_by_block_number(block_number)
+        .ok_or(format!("Block {} is not in the accumulator", block_number))?;
+    let branch = accumulator_trie
+        .generate_block_proof(block_number)
+        .ok_or(format!(
+            "Unable to generate proof for block {}",
+            block_number
+        ))?;
+    let master_branch = accumulator_trie
+        .generate_epoch_proof(&epoch_number)
+        .ok_or(format!(
+            "Unable to generate proof for epoch {}",
+            epoch_number
+        ))?;
+
+    let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+    let master_root = accumulator_trie.get_master_root_hash(&master_accumulator);
+
+    Ok(BlockProofVector {
+        block_number: *block_number,
+        block_hash: epoch_sede.block_hash(),
+        total_difficulty: epoch_sede.total_difficulty(),
+        epoch_index: epoch_number - 1,
+        leaf_index,
+        branch,
+        master_branch,
+        epoch_root: master_accumulator[epoch_number - 1],
+        master_root,
+    })
+}
+
+//proves an epoch root up to the master root
+#[derive(Clone, Debug, Serialize)]
+pub struct EpochProofVector {
+    pub epoch_number: usize,
+    //position of the epoch in the master accumulator
+    pub epoch_index: usize,
+    pub epoch_root: H256,
+    pub master_branch: Vec<H256>,
+    pub master_root: H256,
+}
+
+impl EpochProofVector {
+    pub fn verify(&self) -> bool {
+        verify_epoch_in_master(
+            self.epoch_root,
+            self.epoch_index,
+            &self.master_branch,
+            self.master_root,
+        )
+    }
+}
+
+pub fn generate_epoch_proof_vector<T: TrieTrait>(
+    accumulator_trie: &AccumulatorTrie<T>,
+    epoch_number: &usize,
+) -> Result<EpochProofVector, String> {
+    let master_branch = accumulator_trie
+        .generate_epoch_proof(epoch_number)
+        .ok_or(format!(
+            "Unable to generate proof for epoch {}",
+            epoch_number
+        ))?;
+
+    let master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+    let master_root = accumulator_trie.get_master_root_hash(&master_accumulator);
+
+    Ok(EpochProofVector {
+        epoch_number: *epoch_number,
+        epoch_index: epoch_number - 1,
+        epoch_root: master_accumulator[epoch_number - 1],
+        master_branch,
+        master_root,
+    })
+}
+
+//proves a run of blocks of one epoch with a single multiproof, the lists are in block order
+//helpers prove the blocks up to the epoch root and master_branch the epoch root up to the master root
+#[derive(Clone, Debug, Serialize)]
+pub struct MultiproofVector {
+    pub block_numbers: Vec<u32>,
+    pub block_hashes: Vec<H256>,
+    pub total_difficulties: Vec<U256>,
+    pub epoch_index: usize,
+    pub leaf_indices: Vec<usize>,
+    pub helpers: Vec<H256>,
+    pub master_branch: Vec<H256>,
+    pub epoch_root: H256,
+    pub master_root: H256,
+}
+
+impl MultiproofVector {
+    pub fn verify(&self) -> bool {
+        let multiproof = EpochMultiproof {
+            indices: self.leaf_indices.clone(),
+            leaves: self
+                .block_hashes
+                .iter()
+                .zip(self.total_difficulties.iter())
+                .map(|(block_hash, total_difficulty)| {
+                    EpochSede::new(*total_difficulty, *block_hash)
+                })
+                .collect(),
+            helpers: self.helpers.clone(),
+        };
+
+        self.block_hashes.len() == self.leaf_indices.len()
+            && verify_epoch_multiproof(&multiproof, self.epoch_root)
+            && verify_epoch_in_master(
+                self.epoch_root,
+                self.epoch_index,
+                &self.master_branch,
+                self.master_root,
+            )
+    }
+}
+
+//first_block and last_block are inclusive and have to be in the same epoch
+pub fn generate_multiproof_vector<T: TrieTrait>(
+    accumulator_trie: &AccumulatorTrie<T>,
+    first_block: &u32,
+    last_block: &u32,
+) -> Result<MultiproofVector, String> {
+    let (epoch_number, first_index) = accumulator_trie.get_epoch_number_and_index(first_block);
+    let (last_epoch_number, last_index) = accumulator_trie.get_epoch_number_and_index(last_block);
+    if first_block > last_block || epoch_number != last_epoch_number {
+        return Err(format!(
+            "Blocks {} to {} are not a range within one epoch",
+            first_block, last_block
+        ));
+    }
+
+    let leaf_indices: Vec<usize> = (first_index..=last_index).collect();
+    let multiproof = accumulator_trie
+        .generate_epoch_multiproof(&epoch_number, &leaf_indices)
+        .ok_or(format!(
+            "Unable to generate multiproof for blocks {} to {}",
+            first_block, last_block
+        ))?;
+    let epoch_proof_vector = generate_epoch_proof_vector(accumulator_trie, &epoch_number)?;
+
+    Ok(MultiproofVector {
+        block_numbers: (*first_block..=*last_block).collect(),
+        block_hashes: multiproof.leaves.iter().map(|x| x.block_hash()).collect(),
+        total_difficulties: multiproof
+            .leaves
+            .iter()
+            .map(|x| x.total_difficulty())
+            .collect(),
+        epoch_index: epoch_proof_vector.epoch_index,
+        leaf_indices: multiproof.indices,
+        helpers: multiproof.helpers,
+        master_branch: epoch_proof_vector.master_branch,
+        epoch_root: epoch_proof_vector.epoch_root,
+        master_root: epoch_proof_vector.master_root,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accumulator_storage::memory_storage::AccumulatorMemoryStorage;
+    use accumulator_trie::merkle_tree::{get_epoch_tree, MerkleTree, MASTER_TREE_DEPTH};
+    use accumulator_trie::trie::Trie;
+
+    fn get_block_proof_vector() -> BlockProofVector {
+        let epoch_accumulator: Vec<EpochSede> = (0..5)
+            .map(|x| EpochSede::new(U256::from(x * 11), H256::from_low_u64_be(x + 1)))
+            .collect();
+        let epoch_tree = get_epoch_tree(&epoch_accumulator);
+
+        let master_accumulator = vec![H256::from_low_u64_be(7), epoch_tree.hash_tree_root()];
+        let master_tree = MerkleTree::new(master_accumulator.clone(), MASTER_TREE_DEPTH);
+
+        BlockProofVector {
+            block_number: 8195,
+            block_hash: epoch_accumulator[3].block_hash(),
+            total_difficulty: epoch_accumulator[3].total_difficulty(),
+            epoch_index: 1,
+            leaf_index: 3,
+            branch: epoch_tree.branch(3),
+            master_branch: master_tree.branch(1),
+            epoch_root: master_accumulator[1],
+            master_root: master_tree.hash_tree_root(),
+        }
+    }
+
+    #[test]
+    fn block_proof_vector_verifies() {
+        let mut vector = get_block_proof_vector();
+        assert!(vector.verify());
+
+        vector.epoch_index = 0;
+        assert!(!vector.verify());
+    }
+
+    #[test]
+    fn block_proof_vector_is_hex_encoded() {
+        let vector = get_block_proof_vector();
+        let json: serde_json::Value = serde_json::to_value(&vector).unwrap();
+
+        assert_eq!(format!("{:?}", vector.block_hash), json["block_hash"]);
+        assert_eq!("0x21", json["total_difficulty"]);
+        assert_eq!(format!("{:?}", vector.master_root), json["master_root"]);
+        assert_eq!(12, json["branch"].as_array().unwrap().len());
+        assert_eq!(25, json["master_branch"].as_array().unwrap().len());
+    }
+
+    //two full epochs and a partial third one of 4 blocks each
+    fn init_accumulator_trie() -> AccumulatorTrie<Trie<AccumulatorMemoryStorage>> {
+        let mut accumulator_trie = AccumulatorTrie::new(Trie::new(AccumulatorMemoryStorage::new()));
+        accumulator_trie.epoch_size = 4;
+
+        let mut master_accumulator = accumulator_trie.get_master_accumulator().unwrap();
+        for (i, epoch_length) in [4, 4, 2].iter().enumerate() {
+            let epoch_accumulator: Vec<EpochSede> = (0..*epoch_length)
+                .map(|x| {
+                    EpochSede::new(U256::from(x * 3), H256::from_low_u64_be(i as u64 * 10 + x))
+                })
+                .collect();
+            accumulator_trie.store_epoch_accumulator_by_epoch_number(&(i + 1), &epoch_accumulator);
+            master_accumulator.push(accumulator_trie.get_tree_root_hash(&epoch_accumulator));
+        }
+        accumulator_trie.store_master_accumulator(&master_accumulator);
+
+        accumulator_trie
+    }
+
+    #[test]
+    fn epoch_proof_vector_verifies() {
+        let accumulator_trie = init_accumulator_trie();
+        let mut vector = generate_epoch_proof_vector(&accumulator_trie, &2).unwrap();
+        assert!(vector.verify());
+
+        let json: serde_json::Value = serde_json::to_value(&vector).unwrap();
+        assert_eq!(format!("{:?}", vector.epoch_root), json["epoch_root"]);
+        assert_eq!(25, json["master_branch"].as_array().unwrap().len());
+
+        vector.epoch_index = 0;
+        assert!(!vector.verify());
+        assert!(generate_epoch_proof_vector(&accumulator_trie, &4).is_err());
+    }
+
+    #[test]
+    fn multiproof_vector_verifies() {
+        let accumulator_trie = init_accumulator_trie();
+        let mut vector = generate_multiproof_vector(&accumulator_trie, &5, &7).unwrap();
+        assert_eq!(vec![5, 6, 7], vector.block_numbers);
+        assert_eq!(vec![1, 2, 3], vector.leaf_indices);
+        assert!(vector.verify());
+
+        let json: serde_json::Value = serde_json::to_value(&vector).unwrap();
+        assert_eq!("0x6", json["total_difficulties"][1]);
+
+        vector.block_hashes[2] = H256::zero();
+        assert!(!vector.verify());
+    }
+
+    #[test]
+    fn multiproof_vector_fails_across_epochs() {
+        let accumulator_trie = init_accumulator_trie();
+
+        assert!(generate_multiproof_vector(&accumulator_trie, &3, &4).is_err());
+        assert!(generate_multiproof_vector(&accumulator_trie, &6, &5).is_err());
+        //block 10 is not in the partial epoch yet
+        assert!(generate_multiproof_vector(&accumulator_trie, &8, &10).is_err());
+    }
+}
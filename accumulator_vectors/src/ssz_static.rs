@@ -0,0 +1,90 @@
+use ethereum_types::H256;
+use serde_derive::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize)]
+pub struct EpochSedeValue {
+    pub block_hash: H256,
+    //uint256 is written as a decimal string like the consensus spec tests do
+    pub total_difficulty: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Roots {
+    root: H256,
+}
+
+//writes serialized.ssz_snappy, value.yaml and roots.yaml in the layout of the consensus spec tests
+//<base_path>/ssz_static/<type_name>/ssz_accumulator/<case_name>/
+pub fn write_ssz_static_case<V: serde::Serialize>(
+    base_path: &str,
+    type_name: &str,
+    case_name: &str,
+    serialized: &[u8],
+    value: &V,
+    root: H256,
+) -> PathBuf {
+    let case_path = get_case_path(base_path, type_name, case_name);
+    std::fs::create_dir_all(&case_path).unwrap();
+
+    let compressed = snap::raw::Encoder::new()
+        .compress_vec(serialized)
+        .expect("Unable to snappy compress the serialized value");
+    std::fs::write(case_path.join("serialized.ssz_snappy"), compressed).unwrap();
+
+    let value_yaml = serde_yaml::to_string(value).unwrap();
+    std::fs::write(case_path.join("value.yaml"), value_yaml).unwrap();
+
+    let roots_yaml = serde_yaml::to_string(&Roots { root }).unwrap();
+    std::fs::write(case_path.join("roots.yaml"), roots_yaml).unwrap();
+
+    case_path
+}
+
+fn get_case_path(base_path: &str, type_name: &str, case_name: &str) -> PathBuf {
+    Path::new(base_path)
+        .join("ssz_static")
+        .join(type_name)
+        .join("ssz_accumulator")
+        .join(case_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_case_in_spec_test_layout() {
+        let base_path = "test_ssz_static";
+        let serialized: Vec<u8> = vec![1, 2, 3, 4, 4, 4, 4, 4];
+        let value = vec![H256::from_low_u64_be(1)];
+        let root = H256::from_low_u64_be(2);
+
+        let case_path = write_ssz_static_case(
+            base_path,
+            "MasterAccumulator",
+            "case_0",
+            &serialized,
+            &value,
+            root,
+        );
+        assert_eq!(
+            Path::new("test_ssz_static/ssz_static/MasterAccumulator/ssz_accumulator/case_0"),
+            case_path
+        );
+
+        let compressed = std::fs::read(case_path.join("serialized.ssz_snappy")).unwrap();
+        let decompressed = snap::raw::Decoder::new()
+            .decompress_vec(&compressed)
+            .unwrap();
+        assert_eq!(serialized, decompressed);
+
+        let roots_yaml = std::fs::read_to_string(case_path.join("roots.yaml")).unwrap();
+        assert!(roots_yaml.contains(&format!("{:?}", root)));
+
+        let value_yaml = std::fs::read_to_string(case_path.join("value.yaml")).unwrap();
+        assert!(value_yaml.contains(&format!("{:?}", value[0])));
+
+        std::fs::remove_dir_all(base_path).unwrap();
+    }
+}
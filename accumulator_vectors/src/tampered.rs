@@ -0,0 +1,243 @@
+use accumulator_trie::epoch_sedes::EpochSede;
+use accumulator_trie::merkle_tree::length_chunk;
+use accumulator_trie::proof::{verify_block_in_epoch, verify_epoch_in_master};
+use ethereum_types::{H256, U256};
+use serde_derive::Serialize;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockProofCase {
+    pub label: String,
+    pub block_hash: H256,
+    pub total_difficulty: U256,
+    pub index: usize,
+    pub branch: Vec<H256>,
+    pub epoch_root: H256,
+    pub expected_valid: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EpochProofCase {
+    pub label: String,
+    pub epoch_root: H256,
+    pub index: usize,
+    pub branch: Vec<H256>,
+    pub master_root: H256,
+    pub expected_valid: bool,
+}
+
+impl BlockProofCase {
+    pub fn verify(&self) -> bool {
+        let leaf = EpochSede::new(self.total_difficulty, self.block_hash);
+        verify_block_in_epoch(leaf, self.index, &self.branch, self.epoch_root)
+    }
+
+    fn tampered(&self, label: &str) -> BlockProofCase {
+        BlockProofCase {
+            label: String::from(label),
+            expected_valid: false,
+            ..self.clone()
+        }
+    }
+}
+
+impl EpochProofCase {
+    pub fn verify(&self) -> bool {
+        verify_epoch_in_master(self.epoch_root, self.index, &self.branch, self.master_root)
+    }
+
+    fn tampered(&self, label: &str) -> EpochProofCase {
+        EpochProofCase {
+            label: String::from(label),
+            expected_valid: false,
+            ..self.clone()
+        }
+    }
+}
+
+//the valid proof comes first, followed by one case per mutation
+//a mutation that still verifies, like swapping two equal siblings, is left out
+pub fn tamper_block_proof(
+    leaf: EpochSede,
+    index: usize,
+    branch: &[H256],
+    epoch_root: H256,
+) -> Vec<BlockProofCase> {
+    let valid = BlockProofCase {
+        label: String::from("valid"),
+        block_hash: leaf.block_hash(),
+        total_difficulty: leaf.total_difficulty(),
+        index,
+        branch: branch.to_vec(),
+        epoch_root,
+        expected_valid: true,
+    };
+
+    let mut flipped_sibling = valid.tampered("flipped_sibling");
+    flip_first_bit(&mut flipped_sibling.branch[0]);
+
+    let mut wrong_index = valid.tampered("wrong_index");
+    wrong_index.index = index ^ 1;
+
+    let mut wrong_block_hash = valid.tampered("wrong_block_hash");
+    flip_first_bit(&mut wrong_block_hash.block_hash);
+
+    let mut wrong_total_difficulty = valid.tampered("wrong_total_difficulty");
+    wrong_total_difficulty.total_difficulty =
+        leaf.total_difficulty().overflowing_add(U256::one()).0;
+
+    let mut truncated_branch = valid.tampered("truncated_branch");
+    truncated_branch.branch.remove(0);
+
+    let mut bad_length_mix_in = valid.tampered("bad_length_mix_in");
+    let length_index = bad_length_mix_in.branch.len() - 1;
+    bad_length_mix_in.branch[length_index] =
+        shifted_length_chunk(&bad_length_mix_in.branch[length_index]);
+
+    let mut wrong_epoch_root = valid.tampered("wrong_epoch_root");
+    flip_first_bit(&mut wrong_epoch_root.epoch_root);
+
+    let tampered = vec![
+        flipped_sibling,
+        wrong_index,
+        wrong_block_hash,
+        wrong_total_difficulty,
+        truncated_branch,
+        bad_length_mix_in,
+        wrong_epoch_root,
+    ];
+
+    let mut cases = vec![valid];
+    cases.extend(tampered.into_iter().filter(|x| !x.verify()));
+    cases
+}
+
+pub fn tamper_epoch_proof(
+    epoch_root: H256,
+    index: usize,
+    branch: &[H256],
+    master_root: H256,
+) -> Vec<EpochProofCase> {
+    let valid = EpochProofCase {
+        label: String::from("valid"),
+        epoch_root,
+        index,
+        branch: branch.to_vec(),
+        master_root,
+        expected_valid: true,
+    };
+
+    let mut flipped_sibling = valid.tampered("flipped_sibling");
+    flip_first_bit(&mut flipped_sibling.branch[0]);
+
+    let mut wrong_index = valid.tampered("wrong_index");
+    wrong_index.index = index ^ 1;
+
+    let mut wrong_epoch_root = valid.tampered("wrong_epoch_root");
+    flip_first_bit(&mut wrong_epoch_root.epoch_root);
+
+    let mut truncated_branch = valid.tampered("truncated_branch");
+    truncated_branch.branch.remove(0);
+
+    let mut bad_length_mix_in = valid.tampered("bad_length_mix_in");
+    let length_index = bad_length_mix_in.branch.len() - 1;
+    bad_length_mix_in.branch[length_index] =
+        shifted_length_chunk(&bad_length_mix_in.branch[length_index]);
+
+    let mut wrong_master_root = valid.tampered("wrong_master_root");
+    flip_first_bit(&mut wrong_master_root.master_root);
+
+    let tampered = vec![
+        flipped_sibling,
+        wrong_index,
+        wrong_epoch_root,
+        truncated_branch,
+        bad_length_mix_in,
+        wrong_master_root,
+    ];
+
+    let mut cases = vec![valid];
+    cases.extend(tampered.into_iter().filter(|x| !x.verify()));
+    cases
+}
+
+//claims one more element than the list has, so the index stays inside the list
+fn shifted_length_chunk(chunk: &H256) -> H256 {
+    let mut length = [0; 8];
+    length.copy_from_slice(&chunk.as_bytes()[0..8]);
+
+    length_chunk(u64::from_le_bytes(length) as usize + 1)
+}
+
+fn flip_first_bit(hash: &mut H256) {
+    hash.as_bytes_mut()[0] ^= 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accumulator_trie::merkle_tree::{get_epoch_tree, MerkleTree, MASTER_TREE_DEPTH};
+
+    #[test]
+    fn tampered_block_proofs_match_expected_result() {
+        let epoch_accumulator: Vec<EpochSede> = (0..7)
+            .map(|x| EpochSede::new(U256::from(x * 3), H256::from_low_u64_be(x + 1)))
+            .collect();
+        let epoch_tree = get_epoch_tree(&epoch_accumulator);
+
+        let cases = tamper_block_proof(
+            epoch_accumulator[5],
+            5,
+            &epoch_tree.branch(5),
+            epoch_tree.hash_tree_root(),
+        );
+
+        assert_eq!(8, cases.len());
+        assert!(cases[0].expected_valid);
+        for case in cases.iter() {
+            assert_eq!(case.expected_valid, case.verify(), "{}", case.label);
+        }
+    }
+
+    #[test]
+    fn tampered_block_proofs_skip_mutations_that_still_verify() {
+        //blocks 4 and 5 are equal, so proving block 5 at index 4 is still valid
+        let mut epoch_accumulator: Vec<EpochSede> = (0..7)
+            .map(|x| EpochSede::new(U256::from(x * 3), H256::from_low_u64_be(x + 1)))
+            .collect();
+        epoch_accumulator[4] = epoch_accumulator[5];
+        let epoch_tree = get_epoch_tree(&epoch_accumulator);
+
+        let cases = tamper_block_proof(
+            epoch_accumulator[5],
+            5,
+            &epoch_tree.branch(5),
+            epoch_tree.hash_tree_root(),
+        );
+
+        assert_eq!(7, cases.len());
+        assert!(cases.iter().all(|x| x.label != "wrong_index"));
+        assert!(cases[0].verify());
+        for case in cases[1..].iter() {
+            assert!(!case.expected_valid && !case.verify(), "{}", case.label);
+        }
+    }
+
+    #[test]
+    fn tampered_epoch_proofs_match_expected_result() {
+        let master_accumulator: Vec<H256> = (0..3).map(|x| H256::from_low_u64_be(x + 1)).collect();
+        let master_tree = MerkleTree::new(master_accumulator.clone(), MASTER_TREE_DEPTH);
+
+        let cases = tamper_epoch_proof(
+            master_accumulator[0],
+            0,
+            &master_tree.branch(0),
+            master_tree.hash_tree_root(),
+        );
+
+        assert_eq!(7, cases.len());
+        assert!(cases[0].expected_valid);
+        for case in cases.iter() {
+            assert_eq!(case.expected_valid, case.verify(), "{}", case.label);
+        }
+    }
+}
@@ -7,6 +7,33 @@ pub struct Config {
     pub master_accumulator_file_path: String,
     pub epoch_accumulator_file_path: String,
     pub starting_block_number: u32,
+    #[serde(default = "default_test_vector_file_path")]
+    pub test_vector_file_path: String,
+    #[serde(default)]
+    pub snappy_compression: bool,
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+    #[serde(default = "default_sled_db_path")]
+    pub sled_db_path: String,
+}
+
+//where the master and epoch accumulators are kept
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    //one file per accumulator under the master and epoch accumulator file paths
+    #[default]
+    File,
+    //one sled database at sled_db_path
+    Sled,
+}
+
+fn default_test_vector_file_path() -> String {
+    String::from("./accumulator_result/test_vectors")
+}
+
+fn default_sled_db_path() -> String {
+    String::from("./accumulator_result/db")
 }
 
 impl Config {